@@ -1,496 +1,5887 @@
 pub mod grandiso {
 
     use petgraph::{
-        graphmap::{DiGraphMap, NodeTrait},
-        EdgeDirection::{Incoming, Outgoing},
+        visit::{
+            Data, EdgeRef, GraphBase, GraphProp, IntoEdgeReferences, IntoEdges, IntoNeighbors,
+            IntoNeighborsDirected, IntoNodeIdentifiers, NodeCount,
+        },
+        Direction::{self, Incoming, Outgoing},
     };
     use std::vec::Vec;
     use std::{
-        collections::{HashMap, HashSet, VecDeque},
-        fmt::Debug,
+        cmp::Ordering,
+        collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+        error::Error,
+        fmt::{self, Debug},
+        hash::Hash,
+        iter::once,
+        ops::Deref,
+        slice,
+        sync::{
+            atomic::{AtomicBool, Ordering as AtomicOrdering},
+            Arc,
+        },
+        time::Instant,
     };
 
-    fn _is_structural_match<T, U, V, W>(
-        _motif_node: T,
-        _host_node: V,
-        _candidate: HashMap<T, V>,
-        _motif: &DiGraphMap<T, U>,
-        _host: &DiGraphMap<V, W>,
+    #[cfg(feature = "parallel")]
+    use rayon::prelude::*;
+    #[cfg(feature = "parallel")]
+    use std::sync::{atomic::AtomicUsize, Mutex};
+
+    /// The graph operations that the search needs from a motif or host graph.
+    ///
+    /// This is implemented for references to any petgraph graph that can
+    /// list both the incoming and the outgoing neighbors of a node, such as
+    /// `&Graph`, `&StableGraph` and `&GraphMap`. Graphs that only list their
+    /// outgoing neighbors, such as `Csr`, can be wrapped in a `Bidirectional`.
+    ///
+    /// Both directed and undirected graphs are supported, but the motif and
+    /// the host of a search must agree on which one they are.
+    pub trait SearchGraph:
+        IntoNeighborsDirected
+        + IntoNodeIdentifiers
+        + IntoEdges
+        + NodeCount
+        + GraphProp
+        + GraphBase<NodeId: Hash + Ord + Debug>
+    {
+    }
+
+    impl<G> SearchGraph for G
+    where
+        G: IntoNeighborsDirected + IntoNodeIdentifiers + IntoEdges + NodeCount + GraphProp,
+        G::NodeId: Hash + Ord + Debug,
+    {
+    }
+
+    /// Adds incoming-neighbor lookups to a graph that only lists the
+    /// outgoing neighbors of a node, such as `petgraph::csr::Csr`.
+    ///
+    /// The incoming adjacency lists are built once, when the wrapper is
+    /// created, so the same wrapped host can be reused for many searches by
+    /// passing `&Bidirectional::new(&csr)` wherever a graph is expected.
+    pub struct Bidirectional<G: GraphBase> {
+        graph: G,
+        incoming: HashMap<G::NodeId, Vec<G::NodeId>>,
+    }
+
+    impl<G> Bidirectional<G>
+    where
+        G: IntoNeighbors + IntoNodeIdentifiers,
+        G::NodeId: Hash + Eq,
+    {
+        /// Wrap a graph, indexing the incoming neighbors of every node.
+        pub fn new(graph: G) -> Self {
+            let mut incoming: HashMap<G::NodeId, Vec<G::NodeId>> = HashMap::new();
+            for node in graph.node_identifiers() {
+                for neighbor in graph.neighbors(node) {
+                    incoming.entry(neighbor).or_default().push(node);
+                }
+            }
+            Bidirectional { graph, incoming }
+        }
+    }
+
+    impl<G: GraphBase> GraphBase for Bidirectional<G> {
+        type NodeId = G::NodeId;
+        type EdgeId = G::EdgeId;
+    }
+
+    impl<G: Data> Data for Bidirectional<G> {
+        type NodeWeight = G::NodeWeight;
+        type EdgeWeight = G::EdgeWeight;
+    }
+
+    impl<G: GraphProp> GraphProp for Bidirectional<G> {
+        type EdgeType = G::EdgeType;
+    }
+
+    impl<G: NodeCount> NodeCount for Bidirectional<G> {
+        fn node_count(&self) -> usize {
+            self.graph.node_count()
+        }
+    }
+
+    impl<G: IntoNeighbors> IntoNeighbors for &Bidirectional<G> {
+        type Neighbors = G::Neighbors;
+
+        fn neighbors(self, a: G::NodeId) -> G::Neighbors {
+            self.graph.neighbors(a)
+        }
+    }
+
+    impl<'b, G> IntoNeighborsDirected for &'b Bidirectional<G>
+    where
+        G: IntoNeighbors,
+        G::NodeId: Hash + Eq,
+    {
+        type NeighborsDirected = BidirectionalNeighbors<'b, G>;
+
+        fn neighbors_directed(self, a: G::NodeId, dir: Direction) -> Self::NeighborsDirected {
+            match dir {
+                Outgoing => BidirectionalNeighbors::Outgoing(self.graph.neighbors(a)),
+                Incoming => BidirectionalNeighbors::Incoming(match self.incoming.get(&a) {
+                    Some(neighbors) => neighbors.iter(),
+                    None => [].iter(),
+                }),
+            }
+        }
+    }
+
+    impl<G: IntoNodeIdentifiers> IntoNodeIdentifiers for &Bidirectional<G> {
+        type NodeIdentifiers = G::NodeIdentifiers;
+
+        fn node_identifiers(self) -> G::NodeIdentifiers {
+            self.graph.node_identifiers()
+        }
+    }
+
+    impl<G: IntoEdgeReferences> IntoEdgeReferences for &Bidirectional<G> {
+        type EdgeRef = G::EdgeRef;
+        type EdgeReferences = G::EdgeReferences;
+
+        fn edge_references(self) -> G::EdgeReferences {
+            self.graph.edge_references()
+        }
+    }
+
+    impl<G: IntoEdges> IntoEdges for &Bidirectional<G> {
+        type Edges = G::Edges;
+
+        fn edges(self, a: G::NodeId) -> G::Edges {
+            self.graph.edges(a)
+        }
+    }
+
+    /// The neighbors of a node in a `Bidirectional` graph, in one direction.
+    pub enum BidirectionalNeighbors<'b, G: IntoNeighbors> {
+        Outgoing(G::Neighbors),
+        Incoming(slice::Iter<'b, G::NodeId>),
+    }
+
+    impl<'b, G: IntoNeighbors> Iterator for BidirectionalNeighbors<'b, G> {
+        type Item = G::NodeId;
+
+        fn next(&mut self) -> Option<G::NodeId> {
+            match self {
+                BidirectionalNeighbors::Outgoing(neighbors) => neighbors.next(),
+                BidirectionalNeighbors::Incoming(neighbors) => neighbors.next().copied(),
+            }
+        }
+    }
+
+    /// The reasons a motif search can't be run.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum GrandIsoError {
+        /// The motif has no nodes.
+        EmptyMotif,
+        /// One of the motif and the host is directed and the other one is
+        /// undirected.
+        MixedDirectedness,
+        /// A forbidden edge has an endpoint that isn't a node of the motif.
+        UnknownForbiddenNode,
+        /// A forbidden edge is also an edge of the motif, so no mapping can
+        /// satisfy both.
+        ForbiddenMotifEdge,
+        /// The search was stopped by its deadline or cancellation flag
+        /// before it could tell whether the graphs are isomorphic.
+        Interrupted,
+    }
+
+    impl fmt::Display for GrandIsoError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                GrandIsoError::EmptyMotif => write!(f, "the motif has no nodes"),
+                GrandIsoError::MixedDirectedness => write!(
+                    f,
+                    "the motif and the host must both be directed or both be undirected"
+                ),
+                GrandIsoError::UnknownForbiddenNode => {
+                    write!(
+                        f,
+                        "a forbidden edge has an endpoint that isn't in the motif"
+                    )
+                }
+                GrandIsoError::ForbiddenMotifEdge => {
+                    write!(f, "a forbidden edge is also an edge of the motif")
+                }
+                GrandIsoError::Interrupted => {
+                    write!(f, "the search was interrupted before it had an answer")
+                }
+            }
+        }
+    }
+
+    impl Error for GrandIsoError {}
+
+    /// The kind of mapping that the motif search looks for.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum MatchMode {
+        /// Every motif edge must have a corresponding host edge, but the host
+        /// may have additional edges between mapped nodes (subgraph
+        /// monomorphism).
+        #[default]
+        Monomorphism,
+        /// Like `Monomorphism`, but a host edge between two mapped nodes that
+        /// has no corresponding motif edge disqualifies the mapping (induced
+        /// subgraph isomorphism).
+        Induced,
+        /// Like `Induced`, but the motif and the host must also have the same
+        /// numbers of nodes and edges, so that every mapping is an
+        /// isomorphism between the two whole graphs.
+        Isomorphism,
+        /// Every motif edge must have a corresponding host edge, but several
+        /// motif nodes may be mapped onto the same host node (graph
+        /// homomorphism). E.g. a motif edge between two nodes that are
+        /// mapped onto one host node needs a self-loop on that host node.
+        ///
+        /// There are usually many more homomorphisms than monomorphisms, and
+        /// host signatures can't rule out any host nodes in this mode.
+        Homomorphism,
+    }
+
+    /// The order in which partial mappings are taken off of the search queue.
+    ///
+    /// All orders find the same mappings, but they may find them in a
+    /// different order, and they need different amounts of memory.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum SearchOrder {
+        /// Grow the oldest partial mapping first (FIFO). The queue holds
+        /// every partial mapping of the widest level of the search at once.
+        #[default]
+        BreadthFirst,
+        /// Grow the newest partial mapping first (LIFO). The queue only
+        /// holds the seeds from one host node and the siblings of the
+        /// partial mappings on the current path, so memory stays bounded by
+        /// the motif size times the largest host degree.
+        DepthFirst,
+        /// Search breadth-first while the queue holds at most this many
+        /// partial mappings, and depth-first while it holds more.
+        Hybrid(usize),
+    }
+
+    /// How the parts of a host match that correspond to different connected
+    /// components of the motif may be connected to each other.
+    ///
+    /// This only matters for motifs with more than one connected component.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum HostConnectivity {
+        /// The matched components may or may not be joined by host edges.
+        #[default]
+        Any,
+        /// The host edges between the matched nodes must join all of the
+        /// matched components together, i.e. the matched host nodes must
+        /// form a (weakly) connected subgraph.
+        Connected,
+        /// No host edge may run between nodes that were matched by
+        /// different motif components.
+        Disconnected,
+    }
+
+    /// How a search treats the symmetries (automorphisms) of the motif.
+    ///
+    /// Every host subgraph that matches the motif matches it once per
+    /// automorphism of the motif, e.g. 6 times for an undirected triangle.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum Symmetry {
+        /// Return every mapping, i.e. each matching host subgraph once per
+        /// automorphism of the motif.
+        #[default]
+        All,
+        /// Return one canonical mapping per matching host subgraph: of all
+        /// the mappings that differ only by an automorphism of the motif,
+        /// the one whose host nodes (listed in the motif's node order) are
+        /// lexicographically smallest.
+        ///
+        /// Without node or edge predicates, this is enforced while the
+        /// mappings are grown, as an order between the host nodes of
+        /// symmetric motif nodes, which cuts the work by up to the number of
+        /// automorphisms. (Predicates may tell symmetric motif nodes apart,
+        /// so with predicates, the mappings are only compared once they are
+        /// complete.)
+        Canonical,
+    }
+
+    /// The heuristic that decides in which order motif nodes are mapped.
+    ///
+    /// The most interesting motif node is used as the seed of the search,
+    /// and, of the motif nodes that are connected to a partial mapping, the
+    /// most interesting one is mapped next. Mapping the most constrained
+    /// nodes first keeps the number of partial mappings down.
+    #[derive(Clone, Debug, Default)]
+    pub enum Interestingness<T> {
+        /// All motif nodes are equally interesting. Of the nodes connected to
+        /// a partial mapping, the one with the most mapped neighbors is
+        /// mapped next.
+        #[default]
+        Uniform,
+        /// Motif nodes with more neighbors are more interesting.
+        Degree,
+        /// Motif nodes that fewer host nodes pass the node predicate for
+        /// are more interesting (e.g. the node with the rarest label first).
+        /// Without a node predicate, this is the same as `Uniform`.
+        Rarest,
+        /// User-supplied scores; higher is more interesting. Nodes that are
+        /// missing from the map get a score of 0.
+        Custom(HashMap<T, f32>),
+    }
+
+    /// A user-supplied predicate that decides whether a motif node may be
+    /// mapped to a host node.
+    ///
+    /// Predicates must be `Send + Sync`, so that a parallel search can share
+    /// them between threads.
+    pub type NodeMatchFn<'a, T, V> = Box<dyn Fn(T, V) -> bool + Send + Sync + 'a>;
+
+    /// A user-supplied predicate that decides whether a host edge may stand
+    /// in for a motif edge, given their weights.
+    pub type EdgeMatchFn<'a, U, W> = Box<dyn Fn(&U, &W) -> bool + Send + Sync + 'a>;
+
+    /// A user-supplied function that assigns a label to every node, for
+    /// comparing the labels of the neighbors of motif and host nodes.
+    pub type LabelFn<'a, N> = Box<dyn Fn(N) -> usize + Send + Sync + 'a>;
+
+    /// A user-supplied function that is told how far a search has got.
+    pub type ProgressFn<'a> = Box<dyn Fn(&SearchProgress) + Send + Sync + 'a>;
+
+    /// A snapshot of how far a search has got, as passed to a progress
+    /// callback.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct SearchProgress {
+        /// The number of partial mappings waiting to be expanded
+        pub queue_len: usize,
+        /// The number of partial mappings expanded so far
+        pub expanded: usize,
+        /// The number of complete mappings found so far
+        pub found: usize,
+        /// The number of motif nodes that were mapped in the partial mapping
+        /// that was just expanded
+        pub depth: usize,
+    }
+
+    /// Configuration for a motif search.
+    ///
+    /// The defaults reproduce the behavior of `find_motifs`; use the builder
+    /// methods to change them.
+    pub struct SearchOptions<'a, T, U, V, W> {
+        mode: MatchMode,
+        host_connectivity: HostConnectivity,
+        search_order: SearchOrder,
+        symmetry: Symmetry,
+        interestingness: Interestingness<T>,
+        host_signatures: Option<&'a HostSignatures<V>>,
+        motif_labels: Option<LabelFn<'a, T>>,
+        node_match: Option<NodeMatchFn<'a, T, V>>,
+        edge_match: Option<EdgeMatchFn<'a, U, W>>,
+        deterministic: bool,
+        limit: Option<usize>,
+        deadline: Option<Instant>,
+        cancel: Option<Arc<AtomicBool>>,
+        progress: Option<(usize, ProgressFn<'a>)>,
+        collect_stats: bool,
+        forbidden_edges: Vec<(T, T)>,
+    }
+
+    impl<'a, T, U, V, W> SearchOptions<'a, T, U, V, W> {
+        /// Create a new set of search options with the default values.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Set the kind of mapping to search for.
+        pub fn mode(mut self, mode: MatchMode) -> Self {
+            self.mode = mode;
+            self
+        }
+
+        /// Set how the matches of different motif components may be
+        /// connected to each other in the host.
+        pub fn host_connectivity(mut self, host_connectivity: HostConnectivity) -> Self {
+            self.host_connectivity = host_connectivity;
+            self
+        }
+
+        /// Set the order in which partial mappings are grown, e.g. to bound
+        /// the memory that the search queue takes up.
+        pub fn search_order(mut self, search_order: SearchOrder) -> Self {
+            self.search_order = search_order;
+            self
+        }
+
+        /// Set whether mappings that only differ by an automorphism of the
+        /// motif are all returned, or only one of them.
+        pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+            self.symmetry = symmetry;
+            self
+        }
+
+        /// Set the heuristic that decides in which order motif nodes are
+        /// mapped. This changes how fast a search runs, but not its results.
+        pub fn interestingness(mut self, interestingness: Interestingness<T>) -> Self {
+            self.interestingness = interestingness;
+            self
+        }
+
+        /// Use precomputed host node signatures to rule out host nodes
+        /// before any partial mapping is built onto them.
+        ///
+        /// The signatures must have been computed for the host graph that
+        /// is searched.
+        pub fn host_signatures(mut self, host_signatures: &'a HostSignatures<V>) -> Self {
+            self.host_signatures = Some(host_signatures);
+            self
+        }
+
+        /// Label the motif nodes, for comparison with the neighbor labels in
+        /// host signatures built by `HostSignatures::with_labels`.
+        ///
+        /// The labels must be ones that the node predicate requires to be
+        /// equal between a motif node and its host node; otherwise, valid
+        /// mappings may be ruled out.
+        pub fn motif_labels<F>(mut self, motif_labels: F) -> Self
+        where
+            F: Fn(T) -> usize + Send + Sync + 'a,
+        {
+            self.motif_labels = Some(Box::new(motif_labels));
+            self
+        }
+
+        /// Only map a motif node onto a host node if `node_match` returns
+        /// true for the pair.
+        ///
+        /// The predicate receives the motif node and the host node, and is
+        /// typically used to compare node attributes (e.g. looking both nodes
+        /// up in a table of labels). It is checked for the first node of
+        /// every mapping as well as for every node added afterwards.
+        pub fn node_match<F>(mut self, node_match: F) -> Self
+        where
+            F: Fn(T, V) -> bool + Send + Sync + 'a,
+        {
+            self.node_match = Some(Box::new(node_match));
+            self
+        }
+
+        /// Only map a motif edge onto a host edge if `edge_match` returns
+        /// true for their weights.
+        ///
+        /// The predicate receives the motif edge weight and the host edge
+        /// weight. For example, `.edge_match(|a, b| a == b)` requires the
+        /// weights to be equal. It is checked for every motif edge, including
+        /// self-loops.
+        pub fn edge_match<F>(mut self, edge_match: F) -> Self
+        where
+            F: Fn(&U, &W) -> bool + Send + Sync + 'a,
+        {
+            self.edge_match = Some(Box::new(edge_match));
+            self
+        }
+
+        /// Make a parallel search return its mappings in the same order on
+        /// every run: the order of a breadth-first `find_motifs`.
+        ///
+        /// Only the parallel search (with the `parallel` feature) is
+        /// affected; the serial search is always deterministic. Without this
+        /// option, the parallel search hands back mappings in whatever order
+        /// its threads happen to finish them in, which is a little faster.
+        pub fn deterministic(mut self, deterministic: bool) -> Self {
+            self.deterministic = deterministic;
+            self
+        }
+
+        /// Stop the search once `limit` mappings have been found, instead of
+        /// searching the whole host.
+        ///
+        /// Counts stop at the limit, too, so `count_motifs_with_options`
+        /// can tell whether a motif appears at least `limit` times. Which
+        /// mappings are found first depends on the search order and the
+        /// interestingness heuristic (and, in a parallel search that isn't
+        /// `deterministic`, on timing).
+        pub fn limit(mut self, limit: usize) -> Self {
+            self.limit = Some(limit);
+            self
+        }
+
+        /// Stop the search once `deadline` has passed.
+        ///
+        /// The search checks the clock every few partial mappings, so it
+        /// may run a little past the deadline. Whatever was found until then
+        /// is still returned; the `*_report` functions (and
+        /// `MotifIter::is_interrupted`) tell whether the search was cut
+        /// short.
+        pub fn deadline(mut self, deadline: Instant) -> Self {
+            self.deadline = Some(deadline);
+            self
+        }
+
+        /// Stop the search once `cancel` is set to true, e.g. from another
+        /// thread.
+        ///
+        /// Like `deadline`, the flag is checked every few partial mappings,
+        /// and whatever was found until then is still returned.
+        pub fn cancel(mut self, cancel: Arc<AtomicBool>) -> Self {
+            self.cancel = Some(cancel);
+            self
+        }
+
+        /// Call `progress` after every `every` partial mappings that are
+        /// expanded, e.g. to show how a long search is getting on or to spot
+        /// a runaway one.
+        ///
+        /// In a parallel search, the callback is called from whichever
+        /// thread did the expanding.
+        pub fn progress<F>(mut self, every: usize, progress: F) -> Self
+        where
+            F: Fn(&SearchProgress) + Send + Sync + 'a,
+        {
+            self.progress = Some((every.max(1), Box::new(progress)));
+            self
+        }
+
+        /// Collect `SearchStats` while searching, to be returned by the
+        /// `*_report` functions (or `MotifIter::stats`).
+        pub fn collect_stats(mut self, collect_stats: bool) -> Self {
+            self.collect_stats = collect_stats;
+            self
+        }
+
+        /// Forbid a host edge between the host nodes of `source` and
+        /// `target`, e.g. to find "A -> B but not B -> A" without switching
+        /// to an induced search.
+        ///
+        /// The edge is checked as soon as both of its endpoints are mapped,
+        /// so partial mappings that break it are never grown any further.
+        /// In an undirected search, the edge is forbidden in both
+        /// directions. Call this once for every forbidden edge. The
+        /// forbidden edge must not be an edge of the motif itself.
+        pub fn forbidden_edge(mut self, source: T, target: T) -> Self {
+            self.forbidden_edges.push((source, target));
+            self
+        }
+    }
+
+    impl<'a, T, U, V, W> Default for SearchOptions<'a, T, U, V, W> {
+        fn default() -> Self {
+            SearchOptions {
+                mode: MatchMode::default(),
+                host_connectivity: HostConnectivity::default(),
+                search_order: SearchOrder::default(),
+                symmetry: Symmetry::default(),
+                interestingness: Interestingness::default(),
+                host_signatures: None,
+                motif_labels: None,
+                node_match: None,
+                edge_match: None,
+                deterministic: false,
+                limit: None,
+                deadline: None,
+                cancel: None,
+                progress: None,
+                collect_stats: false,
+                forbidden_edges: Vec::new(),
+            }
+        }
+    }
+
+    impl<'a, T: Debug, U, V, W> Debug for SearchOptions<'a, T, U, V, W> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("SearchOptions")
+                .field("mode", &self.mode)
+                .field("host_connectivity", &self.host_connectivity)
+                .field("search_order", &self.search_order)
+                .field("symmetry", &self.symmetry)
+                .field("interestingness", &self.interestingness)
+                .field("host_signatures", &self.host_signatures.is_some())
+                .field("motif_labels", &self.motif_labels.is_some())
+                .field("node_match", &self.node_match.is_some())
+                .field("edge_match", &self.edge_match.is_some())
+                .field("deterministic", &self.deterministic)
+                .field("limit", &self.limit)
+                .field("deadline", &self.deadline)
+                .field("cancel", &self.cancel)
+                .field("progress", &self.progress.as_ref().map(|(every, _)| every))
+                .field("collect_stats", &self.collect_stats)
+                .field("forbidden_edges", &self.forbidden_edges)
+                .finish()
+        }
+    }
+
+    /// Static features of a node that any host node it is mapped onto must
+    /// match or exceed.
+    #[derive(Clone, Debug)]
+    struct NodeSignature {
+        /// The number of distinct incoming neighbors
+        in_degree: usize,
+        /// The number of distinct outgoing neighbors
+        out_degree: usize,
+        /// The number of triangles (ignoring edge directions) through the node
+        triangles: usize,
+        /// The number of distinct neighbors with each label
+        neighbor_labels: HashMap<usize, usize>,
+    }
+
+    /// Precomputed signatures of the nodes of a host graph, i.e. their
+    /// degrees, triangle counts and (optionally) the labels of their
+    /// neighbors.
+    ///
+    /// Computing these takes time, so they're only used if passed to
+    /// `SearchOptions::host_signatures`. Build them once per host and reuse
+    /// them across searches. They let the search drop host nodes that
+    /// can't match a motif node before any partial mapping is created, which
+    /// pays off most on hosts with a few high-degree hubs.
+    #[derive(Clone, Debug)]
+    pub struct HostSignatures<V> {
+        signatures: HashMap<V, NodeSignature>,
+        labeled: bool,
+    }
+
+    impl<V: Hash + Eq> HostSignatures<V> {
+        /// Compute the degree and triangle signatures of every host node.
+        pub fn new<H>(host: H) -> Self
+        where
+            H: SearchGraph<NodeId = V>,
+        {
+            HostSignatures {
+                signatures: graph_signatures(host, None),
+                labeled: false,
+            }
+        }
+
+        /// Compute the signatures of every host node, including how many of
+        /// its neighbors have each label. Use `SearchOptions::motif_labels`
+        /// to label the motif nodes the same way.
+        pub fn with_labels<H, F>(host: H, labels: F) -> Self
+        where
+            H: SearchGraph<NodeId = V>,
+            F: Fn(V) -> usize,
+        {
+            HostSignatures {
+                signatures: graph_signatures(host, Some(&labels)),
+                labeled: true,
+            }
+        }
+    }
+
+    /// Check whether a motif node may be mapped onto a host node, according
+    /// to the user-supplied node predicate (if any).
+    ///
+    /// # Arguments
+    ///
+    /// * `motif_node` - The motif node to be mapped
+    /// * `host_node` - The host node to map it onto
+    /// * `options` - The search configuration
+    ///
+    fn is_structural_match<T, U, V, W>(
+        motif_node: T,
+        host_node: V,
+        options: &SearchOptions<T, U, V, W>,
+    ) -> bool {
+        match &options.node_match {
+            Some(node_match) => node_match(motif_node, host_node),
+            None => true,
+        }
+    }
+
+    /// Check whether a host edge may stand in for a motif edge, according
+    /// to the user-supplied edge predicate (if any).
+    ///
+    /// # Arguments
+    ///
+    /// * `motif_weight` - The weight of the motif edge
+    /// * `host_weight` - The weight of the host edge
+    /// * `options` - The search configuration
+    ///
+    fn is_edge_match<T, U, V, W>(
+        motif_weight: &U,
+        host_weight: &W,
+        options: &SearchOptions<T, U, V, W>,
+    ) -> bool {
+        match &options.edge_match {
+            Some(edge_match) => edge_match(motif_weight, host_weight),
+            None => true,
+        }
+    }
+
+    /// The results of a search, together with how the search went.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct SearchReport<R> {
+        /// The mappings that were found, or their number
+        pub results: R,
+        /// Whether the search was cut short by its deadline or cancellation
+        /// flag, in which case `results` only holds what had been found by
+        /// then
+        pub interrupted: bool,
+        /// Statistics about the search, if the options asked for them
+        pub stats: Option<SearchStats>,
+    }
+
+    /// Statistics about where a search spent its time, e.g. for tuning the
+    /// interestingness heuristic.
+    ///
+    /// The per-depth counts are indexed by the number of motif nodes in the
+    /// partial mappings that were grown.
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct SearchStats {
+        /// The number of two-node partial mappings that the search was
+        /// seeded with (zero if it wasn't seeded from host edges)
+        pub seeds: usize,
+        /// The number of host nodes that partial mappings of each depth
+        /// could be grown with
+        pub candidates: Vec<usize>,
+        /// The number of host nodes that passed every other check, but were
+        /// rejected by the final edge check (or, when they would complete a
+        /// mapping, by the connectivity or canonical check), at each depth
+        pub rejected: Vec<usize>,
+        /// The largest number of partial mappings that waited in the queue
+        /// at once
+        pub peak_queue_len: usize,
+    }
+
+    impl SearchStats {
+        fn new(motif_size: usize) -> Self {
+            SearchStats {
+                seeds: 0,
+                candidates: vec![0; motif_size],
+                rejected: vec![0; motif_size],
+                peak_queue_len: 0,
+            }
+        }
+    }
+
+    /// A mapping from motif nodes to the host nodes they are matched with.
+    pub type Mapping<G, H> = HashMap<<G as GraphBase>::NodeId, <H as GraphBase>::NodeId>;
+
+    /// The search options for a particular pair of motif and host graphs.
+    type OptionsFor<'a, G, H> = SearchOptions<
+        'a,
+        <G as GraphBase>::NodeId,
+        <G as Data>::EdgeWeight,
+        <H as GraphBase>::NodeId,
+        <H as Data>::EdgeWeight,
+    >;
+
+    /// The precomputed motif information for a particular pair of motif and
+    /// host graphs.
+    type InfoFor<G, H> = MotifInfo<
+        <G as GraphBase>::NodeId,
+        <H as GraphBase>::NodeId,
+        <H as IntoEdgeReferences>::EdgeRef,
+    >;
+
+    /// The host edges from one host node to another, by their endpoints.
+    type HostEdges<V, E> = HashMap<(V, V), Vec<E>>;
+
+    /// Check whether the host has an edge from `host_u` to `host_v`.
+    ///
+    /// This is a lookup in the host edge index, if the search built one.
+    /// Otherwise, the outgoing neighbors of `host_u` and the incoming
+    /// neighbors of `host_v` are scanned side by side, so that a hub on one
+    /// end doesn't have to be scanned in full.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host graph
+    /// * `host_u`, `host_v` - The endpoints of the host edge
+    /// * `info` - The precomputed motif information
+    ///
+    fn has_host_edge<H, N>(
+        host: H,
+        (host_u, host_v): (H::NodeId, H::NodeId),
+        info: &MotifInfo<N, H::NodeId, H::EdgeRef>,
+    ) -> bool
+    where
+        H: SearchGraph,
+    {
+        if let Some(host_edges) = &info.host_edges {
+            return host_edges.contains_key(&(host_u, host_v));
+        }
+        let mut outgoing = host.neighbors_directed(host_u, Outgoing);
+        let mut incoming = host.neighbors_directed(host_v, Incoming);
+        loop {
+            match (outgoing.next(), incoming.next()) {
+                (Some(n), _) if n == host_v => return true,
+                (_, Some(n)) if n == host_u => return true,
+                (None, _) | (_, None) => return false,
+                _ => {}
+            }
+        }
+    }
+
+    /// Index the host edges by their endpoints, so that the edges from one
+    /// host node to another can be looked up without scanning either node's
+    /// neighborhood. (Undirected edges are indexed in both orientations.)
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The host graph
+    ///
+    fn index_host_edges<H>(host: H) -> HostEdges<H::NodeId, H::EdgeRef>
+    where
+        H: SearchGraph,
+    {
+        let mut host_edges: HostEdges<_, _> = HashMap::new();
+        for edge in host.edge_references() {
+            host_edges
+                .entry((edge.source(), edge.target()))
+                .or_default()
+                .push(edge);
+            if !host.is_directed() && edge.source() != edge.target() {
+                host_edges
+                    .entry((edge.target(), edge.source()))
+                    .or_default()
+                    .push(edge);
+            }
+        }
+        host_edges
+    }
+
+    /// Check that every motif edge from `u` to `v` has a host edge from
+    /// `host_u` to `host_v` that satisfies the edge predicate.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `u`, `v` - The endpoints of the motif edge(s)
+    /// * `host` - The host graph
+    /// * `host_u`, `host_v` - The host nodes that `u` and `v` are mapped onto
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn has_matching_edges<G, H>(
+        motif: G,
+        (u, v): (G::NodeId, G::NodeId),
+        host: H,
+        (host_u, host_v): (H::NodeId, H::NodeId),
+        info: &InfoFor<G, H>,
+        options: &OptionsFor<G, H>,
+    ) -> bool
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let mut motif_edges = motif
+            .edges(u)
+            .filter(|motif_edge| motif_edge.target() == v)
+            .peekable();
+        match &info.host_edges {
+            // Without an edge predicate, any host edge will do.
+            None => motif_edges.peek().is_none() || has_host_edge(host, (host_u, host_v), info),
+            Some(host_edges) => {
+                let host_edges = host_edges
+                    .get(&(host_u, host_v))
+                    .map_or(&[][..], Vec::as_slice);
+                motif_edges.all(|motif_edge| {
+                    host_edges.iter().any(|host_edge| {
+                        is_edge_match(motif_edge.weight(), host_edge.weight(), options)
+                    })
+                })
+            }
+        }
+    }
+
+    /// Check that mapping a motif node onto a host node keeps the edges
+    /// touching it consistent with the host graph.
+    ///
+    /// Every pair of mapped motif nodes is checked exactly once: when the
+    /// second of the two is added to the candidate. This includes the pair
+    /// of the new node with itself, i.e. its self-loop. (Edges between the
+    /// new node and its already-mapped motif neighbors are the exception:
+    /// `get_next_candidates` only proposes host nodes that supply them, so
+    /// they aren't checked a second time here.)
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The candidate mapping, which does not contain `node`
+    /// * `node` - The motif node that is being added
+    /// * `host_node` - The host node that `node` would be mapped onto
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn is_edge_consistent<G, H>(
+        candidate: &HashMap<G::NodeId, H::NodeId>,
+        node: G::NodeId,
+        host_node: H::NodeId,
+        motif: G,
+        host: H,
+        info: &InfoFor<G, H>,
+        options: &OptionsFor<G, H>,
+    ) -> bool
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        // An undirected edge looks the same from both ends, so it only
+        // needs to be checked in one orientation.
+        let orientations = if motif.is_directed() { 2 } else { 1 };
+        let new_pair = (&node, &host_node);
+        candidate
+            .iter()
+            .chain(once(new_pair))
+            .all(|(other, other_host_node)| {
+                [
+                    (node, *other, host_node, *other_host_node),
+                    (*other, node, *other_host_node, host_node),
+                ]
+                .iter()
+                .take(orientations)
+                .all(|&(u, v, host_u, host_v)| {
+                    if info.forbidden_edges.contains(&(u, v))
+                        && has_host_edge(host, (host_u, host_v), info)
+                    {
+                        false
+                    } else if !motif.neighbors_directed(u, Outgoing).any(|n| n == v) {
+                        // There's no motif edge here, so only an induced
+                        // search (or one that keeps the matched components
+                        // apart) cares whether there is a host edge.
+                        let forbidden =
+                            matches!(info.mode, MatchMode::Induced | MatchMode::Isomorphism)
+                                || (options.host_connectivity == HostConnectivity::Disconnected
+                                    && info.components[&u] != info.components[&v]);
+                        !forbidden || !has_host_edge(host, (host_u, host_v), info)
+                    } else if u != v {
+                        // Already verified by get_next_candidates.
+                        true
+                    } else {
+                        has_matching_edges(motif, (u, v), host, (host_u, host_v), info, options)
+                    }
+                })
+            })
+    }
+
+    /// Everything about the motif that the search looks up repeatedly,
+    /// computed once when the search is set up.
+    struct MotifInfo<N, V, E> {
+        /// The kind of mapping that is searched for
+        mode: MatchMode,
+        /// A mapping of some search-order heuristic
+        interestingness: HashMap<N, f32>,
+        /// The component index of every motif node
+        components: HashMap<N, usize>,
+        /// The number of components in the motif
+        component_count: usize,
+        /// The signature of every motif node (only if the host's are known)
+        signatures: Option<HashMap<N, NodeSignature>>,
+        /// The motif nodes, in the order that canonical mappings are
+        /// compared in
+        nodes: Vec<N>,
+        /// The automorphisms of the motif other than the identity, which
+        /// complete mappings are compared under (only for
+        /// `Symmetry::Canonical` searches with predicates)
+        automorphisms: Vec<HashMap<N, N>>,
+        /// For each motif node, the other motif nodes that its host node
+        /// must compare to in a certain way (only for `Symmetry::Canonical`
+        /// searches without predicates)
+        symmetry_constraints: HashMap<N, Vec<(N, Ordering)>>,
+        /// The motif edges that must not be present in the host (in both
+        /// orientations, if the motif is undirected)
+        forbidden_edges: HashSet<(N, N)>,
+        /// The host edges between every pair of host nodes (only if the
+        /// search compares edge weights, or checks for missing host edges)
+        host_edges: Option<HostEdges<V, E>>,
+    }
+
+    /// Turn the automorphisms of a motif into ordering constraints between
+    /// the host nodes of symmetric motif nodes, such that exactly the
+    /// canonical mapping of every set of mappings that only differ by an
+    /// automorphism meets them all.
+    ///
+    /// The first motif node must be mapped onto a smaller host node than
+    /// every other node in its orbit (the nodes that automorphisms move it
+    /// to). Among the automorphisms that keep the first node in place, the
+    /// same goes for the second node, and so on.
+    ///
+    /// # Arguments
+    ///
+    /// * `nodes` - The motif nodes, in the order that mappings are compared in
+    /// * `automorphisms` - All automorphisms of the motif
+    ///
+    fn symmetry_constraints<N>(
+        nodes: &[N],
+        mut automorphisms: Vec<HashMap<N, N>>,
+    ) -> HashMap<N, Vec<(N, Ordering)>>
+    where
+        N: Hash + Eq + Copy,
+    {
+        let mut constraints: HashMap<N, Vec<(N, Ordering)>> = HashMap::new();
+        for node in nodes {
+            if automorphisms.len() <= 1 {
+                break;
+            }
+            let orbit: HashSet<N> = automorphisms
+                .iter()
+                .map(|automorphism| automorphism[node])
+                .filter(|other| other != node)
+                .collect();
+            for other in orbit {
+                constraints
+                    .entry(*node)
+                    .or_default()
+                    .push((other, Ordering::Less));
+                constraints
+                    .entry(other)
+                    .or_default()
+                    .push((*node, Ordering::Greater));
+            }
+            automorphisms.retain(|automorphism| automorphism[node] == *node);
+        }
+        constraints
+    }
+
+    /// Check that mapping a motif node onto a host node keeps the host nodes
+    /// of symmetric motif nodes in the order that canonical mappings have.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The candidate mapping, which does not contain `node`
+    /// * `node` - The motif node to be mapped
+    /// * `host_node` - The host node that `node` would be mapped onto
+    /// * `info` - The precomputed motif information
+    ///
+    fn is_symmetry_ordered<N, V, E>(
+        candidate: &HashMap<N, V>,
+        node: N,
+        host_node: V,
+        info: &MotifInfo<N, V, E>,
     ) -> bool
     where
-        T: NodeTrait,
-        U: NodeTrait,
-        V: NodeTrait,
-        W: NodeTrait,
+        N: Hash + Eq,
+        V: Ord,
     {
-        true
+        match info.symmetry_constraints.get(&node) {
+            Some(constraints) => constraints.iter().all(|(other, ordering)| {
+                candidate.get(other).map_or(true, |other_host_node| {
+                    host_node.cmp(other_host_node) == *ordering
+                })
+            }),
+            None => true,
+        }
+    }
+
+    /// Compute the signature of every node of a graph.
+    ///
+    /// # Arguments
+    ///
+    /// * `graph` - The graph
+    /// * `labels` - The label of every node, if neighbor labels are counted
+    ///
+    fn graph_signatures<G>(
+        graph: G,
+        labels: Option<&dyn Fn(G::NodeId) -> usize>,
+    ) -> HashMap<G::NodeId, NodeSignature>
+    where
+        G: SearchGraph,
+    {
+        // The distinct neighbors of every node, ignoring edge directions and
+        // self-loops.
+        let neighborhoods: HashMap<G::NodeId, HashSet<G::NodeId>> = graph
+            .node_identifiers()
+            .map(|node| {
+                let neighbors = graph
+                    .neighbors_directed(node, Outgoing)
+                    .chain(graph.neighbors_directed(node, Incoming))
+                    .filter(|neighbor| *neighbor != node)
+                    .collect();
+                (node, neighbors)
+            })
+            .collect();
+
+        graph
+            .node_identifiers()
+            .map(|node| {
+                let neighbors = &neighborhoods[&node];
+                // Every triangle is seen twice, once from each of the other
+                // two nodes. Walking the smaller of the two neighborhoods
+                // keeps hubs from scanning each other's neighbors over and
+                // over.
+                let triangles = neighbors
+                    .iter()
+                    .map(|neighbor| {
+                        let theirs = &neighborhoods[neighbor];
+                        let (smaller, larger) = if theirs.len() < neighbors.len() {
+                            (theirs, neighbors)
+                        } else {
+                            (neighbors, theirs)
+                        };
+                        smaller.iter().filter(|n| larger.contains(n)).count()
+                    })
+                    .sum::<usize>()
+                    / 2;
+                let mut neighbor_labels = HashMap::new();
+                if let Some(labels) = labels {
+                    for neighbor in neighbors {
+                        *neighbor_labels.entry(labels(*neighbor)).or_insert(0) += 1;
+                    }
+                }
+                let signature = NodeSignature {
+                    in_degree: graph
+                        .neighbors_directed(node, Incoming)
+                        .collect::<HashSet<_>>()
+                        .len(),
+                    out_degree: graph
+                        .neighbors_directed(node, Outgoing)
+                        .collect::<HashSet<_>>()
+                        .len(),
+                    triangles,
+                    neighbor_labels,
+                };
+                (node, signature)
+            })
+            .collect()
+    }
+
+    /// Check whether a host node's signature allows a motif node to be
+    /// mapped onto it. (Always true if no host signatures were given.)
+    ///
+    /// # Arguments
+    ///
+    /// * `motif_node` - The motif node to be mapped
+    /// * `host_node` - The host node to map it onto
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn is_signature_match<T, U, V, W, E>(
+        motif_node: T,
+        host_node: V,
+        info: &MotifInfo<T, V, E>,
+        options: &SearchOptions<T, U, V, W>,
+    ) -> bool
+    where
+        T: Hash + Eq,
+        V: Hash + Eq,
+    {
+        let (motif_signatures, host_signatures) = match (&info.signatures, options.host_signatures)
+        {
+            (Some(motif_signatures), Some(host_signatures)) => (motif_signatures, host_signatures),
+            _ => return true,
+        };
+        let motif_signature = &motif_signatures[&motif_node];
+        let host_signature = match host_signatures.signatures.get(&host_node) {
+            Some(host_signature) => host_signature,
+            None => return true,
+        };
+
+        // A monomorphism maps the neighbors (and the triangles) of the motif
+        // node onto distinct neighbors (and triangles) of the host node, so
+        // the host node must have at least as many of each.
+        host_signature.in_degree >= motif_signature.in_degree
+            && host_signature.out_degree >= motif_signature.out_degree
+            && host_signature.triangles >= motif_signature.triangles
+            && motif_signature
+                .neighbor_labels
+                .iter()
+                .all(|(label, count)| {
+                    host_signature.neighbor_labels.get(label).unwrap_or(&0) >= count
+                })
+    }
+
+    /// Score every motif node according to the interestingness heuristic in
+    /// the search options.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration
+    ///
+    fn score_interestingness<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> HashMap<G::NodeId, f32>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        motif
+            .node_identifiers()
+            .map(|node| {
+                let score = match &options.interestingness {
+                    Interestingness::Uniform => 1f32,
+                    Interestingness::Degree => motif
+                        .neighbors_directed(node, Incoming)
+                        .chain(motif.neighbors_directed(node, Outgoing))
+                        .collect::<HashSet<_>>()
+                        .len() as f32,
+                    Interestingness::Rarest => match &options.node_match {
+                        Some(node_match) => {
+                            let matches = host
+                                .node_identifiers()
+                                .filter(|host_node| node_match(node, *host_node))
+                                .count();
+                            -(matches as f32)
+                        }
+                        None => 1f32,
+                    },
+                    Interestingness::Custom(scores) => {
+                        scores.get(&node).copied().unwrap_or_default()
+                    }
+                };
+                (node, score)
+            })
+            .collect()
+    }
+
+    /// Choose the motif edge that the search is seeded with.
+    ///
+    /// The motif edge is chosen the same way as the first motif nodes of a
+    /// search: the most interesting motif node (that has a neighbor), and its
+    /// most interesting neighbor. Returns the edge as a (source, target)
+    /// pair, or `None` if the motif has no edges between distinct nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `info` - The precomputed motif information
+    ///
+    fn seed_edge<G, V, E>(
+        motif: G,
+        info: &MotifInfo<G::NodeId, V, E>,
+    ) -> Option<(G::NodeId, G::NodeId)>
+    where
+        G: SearchGraph,
+    {
+        let by_interestingness = |a: &G::NodeId, b: &G::NodeId| {
+            info.interestingness[a]
+                .partial_cmp(&info.interestingness[b])
+                .unwrap_or(Ordering::Equal)
+        };
+        let neighbors = |node: G::NodeId| {
+            motif
+                .neighbors_directed(node, Outgoing)
+                .chain(motif.neighbors_directed(node, Incoming))
+                .filter(move |neighbor| *neighbor != node)
+        };
+        let first_node = motif
+            .node_identifiers()
+            .filter(|node| neighbors(*node).next().is_some())
+            .max_by(by_interestingness)?;
+        let second_node = neighbors(first_node).max_by(by_interestingness)?;
+
+        // Host edges are mapped onto in the direction of a motif edge.
+        if motif
+            .neighbors_directed(first_node, Outgoing)
+            .any(|n| n == second_node)
+        {
+            Some((first_node, second_node))
+        } else {
+            Some((second_node, first_node))
+        }
+    }
+
+    /// Map the seed motif edge onto the outgoing edges of one host node.
+    ///
+    /// Returns the host nodes that the target of the seed edge can be mapped
+    /// onto, if its source is mapped onto `host_source`, along with the
+    /// number of host nodes that passed every other check, but were rejected
+    /// by the final edge check (or by the checks on complete mappings).
+    ///
+    /// # Arguments
+    ///
+    /// * `(source, target)` - The seed motif edge
+    /// * `host_source` - The host node that `source` is mapped onto
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn seed_targets<G, H>(
+        (source, target): (G::NodeId, G::NodeId),
+        host_source: H::NodeId,
+        motif: G,
+        host: H,
+        info: &InfoFor<G, H>,
+        options: &OptionsFor<G, H>,
+    ) -> (Vec<H::NodeId>, usize)
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        if !is_signature_match(source, host_source, info, options) {
+            return (vec![], 0);
+        }
+        // The rest of the checks on the source are only made once a target
+        // gets this far, to spare the node predicate.
+        let mut source_matches = None;
+        let mut is_source_match = || {
+            *source_matches.get_or_insert_with(|| {
+                is_structural_match(source, host_source, options)
+                    && is_edge_consistent(
+                        &HashMap::new(),
+                        source,
+                        host_source,
+                        motif,
+                        host,
+                        info,
+                        options,
+                    )
+            })
+        };
+        let mapping: HashMap<_, _> = once((source, host_source)).collect();
+
+        // Every outgoing host edge is a candidate (which, if the graphs are
+        // undirected, is every edge of the host node). Parallel host edges
+        // must only be seeded once.
+        let mut seen = HashSet::new();
+        let mut rejected = 0;
+        let host_targets = host
+            .neighbors_directed(host_source, Outgoing)
+            .filter(|host_target| seen.insert(*host_target))
+            .filter(|&host_target| {
+                (host_source != host_target || info.mode == MatchMode::Homomorphism)
+                    && is_signature_match(target, host_target, info, options)
+                    && is_source_match()
+                    && is_structural_match(target, host_target, options)
+                    // All of the motif edges between the two nodes, in either
+                    // direction, must be there (is_edge_consistent leaves
+                    // those to get_next_candidates)...
+                    && has_matching_edges(
+                        motif,
+                        (source, target),
+                        host,
+                        (host_source, host_target),
+                        info,
+                        options,
+                    )
+                    && has_matching_edges(
+                        motif,
+                        (target, source),
+                        host,
+                        (host_target, host_source),
+                        info,
+                        options,
+                    )
+                    && is_symmetry_ordered(&mapping, target, host_target, info)
+            })
+            .filter(|&host_target| {
+                // ...as well as everything else, e.g. self-loops. A seed of a
+                // two-node motif is already a complete mapping, so it has to
+                // pass the checks that complete mappings do, too.
+                let consistent =
+                    is_edge_consistent(&mapping, target, host_target, motif, host, info, options)
+                        && (motif.node_count() > 2
+                            || is_complete_match(
+                                &mapping,
+                                target,
+                                host_target,
+                                motif,
+                                host,
+                                info,
+                                options,
+                            ));
+                rejected += !consistent as usize;
+                consistent
+            })
+            .collect();
+        (host_targets, rejected)
+    }
+
+    /// Perform a single iteration of candidate-mapping growth.
+    ///
+    /// Returns the motif node that should be mapped next, along with every
+    /// host node that it can be mapped onto. Each of these host nodes
+    /// extends `candidate` to a new, valid candidate mapping; it's up to the
+    /// caller whether to actually build those mappings (e.g. a count-only
+    /// search doesn't need to build complete ones). The last element is the
+    /// number of host nodes that passed every other check, but were
+    /// rejected by the final edge check.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The partial candidate mapping
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn get_next_candidates<G, H>(
+        candidate: &HashMap<G::NodeId, H::NodeId>,
+        motif: G,
+        host: H,
+        info: &InfoFor<G, H>,
+        options: &OptionsFor<G, H>,
+    ) -> (G::NodeId, Vec<H::NodeId>, usize)
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        // The most interesting node is defined as the node with the maximum
+        // interestingness score, which satisfies the following criteria:
+        // * If the candidate is empty, then any node will do;
+        // * If the candidate has a value, then the most interesting node must
+        //   be connected to a node in the candidate set;
+        // * Unless no unmapped node is connected to the candidate set, which
+        //   means that the candidate covers whole components of the motif.
+        //   Then the next component is started the same way as the first.
+
+        // As a result, the first step is to see if the candidate mapping
+        // already has some nodes assigned. If so, let's pick a motif node
+        // such that it connects to a node in the candidate already, and such
+        // that it maximizes interestingness.
+        // Note that this operation is analogous to the Python here:
+        // https://github.com/aplbrain/grandiso-networkx/blob/b5db289c7b8a681776c264014ec4f31c6431d37d/grandiso/__init__.py#L158
+        // However, in the Python implementation this is performed in
+        // several steps. In Rust, it is all possible to roll this into one
+        // big statement.
+        // println!("{:#?}", candidate);
+        let connected_node = if candidate.is_empty() {
+            None
+        } else {
+            motif
+                .node_identifiers()
+                .filter(|node| !candidate.contains_key(node))
+                .map(|node| {
+                    // Given a node, count how many of its neighbors appear in
+                    // the candidate mapping.
+                    let mapped_neighbors = motif
+                        .neighbors_directed(node, Incoming)
+                        .filter(|v| candidate.contains_key(v))
+                        .count()
+                        + motif
+                            .neighbors_directed(node, Outgoing)
+                            .filter(|v| candidate.contains_key(v))
+                            .count();
+                    (node, mapped_neighbors)
+                })
+                .filter(|&(_node, mapped_neighbors)| mapped_neighbors > 0)
+                // Of those, take the most interesting one. Ties go to the
+                // node with the most mapped neighbors, as every mapped
+                // neighbor rules out more host nodes.
+                .max_by(|(a, a_mapped), (b, b_mapped)| {
+                    info.interestingness[a]
+                        .partial_cmp(&info.interestingness[b])
+                        .unwrap_or(Ordering::Equal)
+                        .then(a_mapped.cmp(b_mapped))
+                })
+                .map(|(node, _mapped_neighbors)| node)
+        };
+
+        match connected_node {
+            None => {
+                // If the candidate is empty, the most interesting node is defined
+                // as the node with the maximum interestingness in general. (If
+                // the candidate has merely run out of connected nodes, it is the
+                // most interesting node of the components that are left.)
+                let most_interesting_node: G::NodeId = motif
+                    .node_identifiers()
+                    .filter(|node| !candidate.contains_key(node))
+                    .max_by(|a, b| {
+                        info.interestingness[a]
+                            .partial_cmp(&info.interestingness[b])
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap();
+
+                // The host node must have at least as many distinct neighbors in
+                // each direction as the motif node does. (The host side may
+                // over-count parallel edges; that only makes this check looser.)
+                // In a homomorphism, all of those neighbors may be mapped onto
+                // the same host node, so it only needs one.
+                let injective = info.mode != MatchMode::Homomorphism;
+                let required_degree = |direction| {
+                    let degree = motif
+                        .neighbors_directed(most_interesting_node, direction)
+                        .collect::<HashSet<_>>()
+                        .len();
+                    if injective {
+                        degree
+                    } else {
+                        degree.min(1)
+                    }
+                };
+                let motif_in_degree = required_degree(Incoming);
+                let motif_out_degree = required_degree(Outgoing);
+
+                // Our first step is to pick a node in the motif graph and
+                // tentatively assign it to every node in the host graph.
+                // For the empty candidate map case, that's all there is to do:
+                // Return the list of ALL possible node mappings whose self-loops
+                // agree with the motif (and, when a new component is started,
+                // that haven't been claimed by the components before it, unless
+                // host nodes may be reused). Host signatures, the degrees and
+                // the node predicate rule out as many of them as they can.
+                let claimed_host_nodes: HashSet<&H::NodeId> = candidate.values().collect();
+                let mut rejected = 0;
+                let next_host_nodes = host
+                    .node_identifiers()
+                    .filter(|n| {
+                        (!injective || !claimed_host_nodes.contains(n))
+                            && is_symmetry_ordered(candidate, most_interesting_node, *n, info)
+                            && is_signature_match(most_interesting_node, *n, info, options)
+                            && host.neighbors_directed(*n, Incoming).count() >= motif_in_degree
+                            && host.neighbors_directed(*n, Outgoing).count() >= motif_out_degree
+                            && is_structural_match(most_interesting_node, *n, options)
+                    })
+                    .filter(|n| {
+                        let consistent = is_edge_consistent(
+                            candidate,
+                            most_interesting_node,
+                            *n,
+                            motif,
+                            host,
+                            info,
+                            options,
+                        );
+                        rejected += !consistent as usize;
+                        consistent
+                    })
+                    .collect();
+                (most_interesting_node, next_host_nodes, rejected)
+            }
+            Some(most_interesting_node) => {
+                // println!("MIN: {:#?}", most_interesting_node);
+
+                // The following is the analog of the following Python:
+                // https://github.com/aplbrain/grandiso-networkx/blob/b5db289c7b8a681776c264014ec4f31c6431d37d/grandiso/__init__.py#L192
+                // Specifically, we now have the name of a node `most_interesting_node`
+                // which we know is connected to the candidate mapping by at least
+                // one edge.
+                // In the next step, get a list of all such edges. We will do this
+                // all in one go: For each edge, we'll first identify the motif
+                // nodes in play, and then we will identify all possible nodes in
+                // the host graph s.t. these edges have valid mappings.
+                // (Parallel motif edges only need to be required once.)
+                let mut required_edges = vec![];
+                let mut required_edges_in = vec![];
+                for neighboring_node in motif.neighbors_directed(most_interesting_node, Outgoing) {
+                    let edge = (most_interesting_node, neighboring_node);
+                    if candidate.contains_key(&neighboring_node) && !required_edges.contains(&edge)
+                    {
+                        required_edges.push(edge);
+                    }
+                }
+                // In an undirected motif, every edge is also an "outgoing" edge
+                // (petgraph lists all neighbors in both directions), so there
+                // are no separate incoming edges to require.
+                if motif.is_directed() {
+                    for neighboring_node in
+                        motif.neighbors_directed(most_interesting_node, Incoming)
+                    {
+                        let edge = (neighboring_node, most_interesting_node);
+                        if candidate.contains_key(&neighboring_node)
+                            && !required_edges_in.contains(&edge)
+                        {
+                            required_edges_in.push(edge);
+                        }
+                    }
+                }
+
+                // Now we have a list of all edges that must exist given the
+                // mapping so far.
+                // Now we must find candidate nodes in the host graph that have the
+                // edges that are required. A host node is a valid candidate only
+                // if it satisfies EVERY required edge, in either direction, so we
+                // take the set intersection of the host neighborhoods implied by
+                // each of the required edges. (Treating the outgoing and incoming
+                // edges separately would both let through nodes that satisfy
+                // only one direction AND report the same mapping twice whenever
+                // a node satisfies both.)
+                if required_edges.is_empty() && required_edges_in.is_empty() {
+                    // py 253: The node was chosen for having a mapped
+                    // neighbor, so there is always an edge to require.
+                    unreachable!(
+                        "motif node {:?} was chosen for having a mapped neighbor, \
+                         but has no edges to the mapped nodes",
+                        most_interesting_node
+                    );
+                }
+
+                // It is impossible for required_edges to have length == 0, because
+                // we DEFINED the most-interesting-node to have nonzero edges.
+                // In the worst-case, there is a single required edge. This is the
+                // worst because it rules out the fewest new nodes from the host:
+                // ANY unclaimed node with a single connection to our M-I-N is a
+                // valid mapping. Every additional required edge whittles the set
+                // of candidates down further.
+                // A host neighbor only counts if the edge that connects it also
+                // satisfies the edge predicate for the corresponding motif edge.
+                // (Without an edge predicate, any edge will do, and we can skip
+                // looking at the host edges altogether.)
+                let check_edges = options.edge_match.is_some();
+                let host_neighborhoods = required_edges
+                    .iter()
+                    .map(|&(m_i_n, neighbor)| {
+                        let host_neighbor = *candidate.get(&neighbor).unwrap();
+                        host.neighbors_directed(host_neighbor, Incoming)
+                            .filter(|n| {
+                                !check_edges
+                                    || has_matching_edges(
+                                        motif,
+                                        (m_i_n, neighbor),
+                                        host,
+                                        (*n, host_neighbor),
+                                        info,
+                                        options,
+                                    )
+                            })
+                            .collect::<Vec<H::NodeId>>()
+                    })
+                    .chain(required_edges_in.iter().map(|&(neighbor, m_i_n)| {
+                        let host_neighbor = *candidate.get(&neighbor).unwrap();
+                        host.neighbors_directed(host_neighbor, Outgoing)
+                            .filter(|n| {
+                                !check_edges
+                                    || has_matching_edges(
+                                        motif,
+                                        (neighbor, m_i_n),
+                                        host,
+                                        (host_neighbor, *n),
+                                        info,
+                                        options,
+                                    )
+                            })
+                            .collect::<Vec<H::NodeId>>()
+                    }));
+
+                // !!!!!!!!!!!!!!!!!!!!! :242 in py
+                // Candidate host nodes are the set intersection of all previous
+                // set entries and the new entries from this edge. The first edge
+                // that we check SETS the candidate set. (The candidates are
+                // kept in the host's own neighbor order, rather than in hash
+                // order, so that the same search always gives the same
+                // results in the same order.)
+                let mut candidate_host_nodes: Option<Vec<H::NodeId>> = None;
+                for neighborhood in host_neighborhoods {
+                    candidate_host_nodes = Some(match candidate_host_nodes {
+                        None => {
+                            // Parallel host edges list a neighbor twice.
+                            let mut seen = HashSet::new();
+                            neighborhood
+                                .into_iter()
+                                .filter(|n| seen.insert(*n))
+                                .collect()
+                        }
+                        Some(nodes) => {
+                            let neighborhood: HashSet<H::NodeId> =
+                                neighborhood.into_iter().collect();
+                            nodes
+                                .into_iter()
+                                .filter(|n| neighborhood.contains(n))
+                                .collect()
+                        }
+                    });
+                }
+
+                // A monomorphism is injective: two motif nodes may never share a
+                // host node. Any host node that has already been claimed by the
+                // candidate mapping is therefore not a valid target for the M-I-N.
+                // (A homomorphism knowingly reuses host nodes.)
+                let injective = info.mode != MatchMode::Homomorphism;
+                let claimed_host_nodes: HashSet<&H::NodeId> = candidate.values().collect();
+                let tentative_host_nodes = candidate_host_nodes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|candidate_node| {
+                        !injective || !claimed_host_nodes.contains(candidate_node)
+                    })
+                    .filter(|candidate_node| {
+                        is_symmetry_ordered(candidate, most_interesting_node, *candidate_node, info)
+                    })
+                    .filter(|candidate_node| {
+                        is_signature_match(most_interesting_node, *candidate_node, info, options)
+                    })
+                    .filter(|candidate_node| {
+                        is_structural_match(most_interesting_node, *candidate_node, options)
+                    });
+
+                // Perform one final filtering step here:
+                // We have determined that all of these NODES belong in the map,
+                // but we have not yet established that all of these edges are
+                // consistent with the motif.
+                // For example, a self-loop on the M-I-N is never a "required
+                // edge" above (the M-I-N isn't in the candidate yet), so a
+                // mapping could be accepted even though the host node lacks the
+                // loop. And in induced mode, a host edge between the new node
+                // and an already-mapped node that the motif lacks must reject
+                // the candidate right away rather than once it is complete.
+                // (An isomorphism search is an induced search of a host of
+                // the same size.)
+                let mut rejected = 0;
+                let next_host_nodes = tentative_host_nodes
+                    .filter(|candidate_node| {
+                        let consistent = is_edge_consistent(
+                            candidate,
+                            most_interesting_node,
+                            *candidate_node,
+                            motif,
+                            host,
+                            info,
+                            options,
+                        );
+                        rejected += !consistent as usize;
+                        consistent
+                    })
+                    .collect();
+                (most_interesting_node, next_host_nodes, rejected)
+            }
+        }
+    }
+
+    /// Check that a search for `motif` in `host` can be run at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    fn check_motif<G, H>(motif: G, host: H) -> Result<(), GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        if motif.is_directed() != host.is_directed() {
+            return Err(GrandIsoError::MixedDirectedness);
+        }
+
+        if motif.node_count() == 0 {
+            return Err(GrandIsoError::EmptyMotif);
+        }
+        Ok(())
+    }
+
+    /// Check that the search options fit the motif.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `options` - The search configuration
+    ///
+    fn check_options<G, H>(motif: G, options: &OptionsFor<G, H>) -> Result<(), GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let is_motif_node = |node: G::NodeId| motif.node_identifiers().any(|n| n == node);
+        if !options
+            .forbidden_edges
+            .iter()
+            .all(|&(u, v)| is_motif_node(u) && is_motif_node(v))
+        {
+            return Err(GrandIsoError::UnknownForbiddenNode);
+        }
+        // (In an undirected motif, the neighbors in either direction are
+        // the same, so this catches a motif edge in the other orientation.)
+        if options
+            .forbidden_edges
+            .iter()
+            .any(|&(u, v)| motif.neighbors_directed(u, Outgoing).any(|n| n == v))
+        {
+            return Err(GrandIsoError::ForbiddenMotifEdge);
+        }
+        Ok(())
+    }
+
+    /// Label every motif node with the index of its (weakly) connected
+    /// component.
+    ///
+    /// Returns the labels along with the number of components.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    ///
+    fn find_components<G>(motif: G) -> (HashMap<G::NodeId, usize>, usize)
+    where
+        G: SearchGraph,
+    {
+        let mut components = HashMap::new();
+        let mut component_count = 0;
+        for start in motif.node_identifiers() {
+            if components.contains_key(&start) {
+                continue;
+            }
+            // Walk the motif from this node, ignoring edge directions.
+            components.insert(start, component_count);
+            let mut stack = vec![start];
+            while let Some(node) = stack.pop() {
+                for neighbor in motif
+                    .neighbors_directed(node, Outgoing)
+                    .chain(motif.neighbors_directed(node, Incoming))
+                {
+                    if let Entry::Vacant(entry) = components.entry(neighbor) {
+                        entry.insert(component_count);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            component_count += 1;
+        }
+        (components, component_count)
+    }
+
+    /// Check that the host edges between the nodes of a complete mapping
+    /// join all of the motif's components together.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The candidate mapping, which does not contain `node`
+    /// * `node` - The last motif node to be mapped
+    /// * `host_node` - The host node that `node` would be mapped onto
+    /// * `host` - The host graph
+    /// * `components` - The component index of every motif node
+    /// * `component_count` - The number of components in the motif
+    ///
+    fn joins_components<G, H>(
+        candidate: &HashMap<G::NodeId, H::NodeId>,
+        node: G::NodeId,
+        host_node: H::NodeId,
+        host: H,
+        components: &HashMap<G::NodeId, usize>,
+        component_count: usize,
+    ) -> bool
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        // Look up the motif components of every matched host node (there
+        // can be several, if host nodes are reused)...
+        let mut host_components: HashMap<H::NodeId, Vec<usize>> = HashMap::new();
+        for (motif_node, host_node) in candidate.iter().chain(once((&node, &host_node))) {
+            host_components
+                .entry(*host_node)
+                .or_default()
+                .push(components[motif_node]);
+        }
+
+        // ...and walk the matched host nodes, ignoring edge directions, to
+        // see which components can be reached from the first one.
+        let mut reached: HashSet<usize> = HashSet::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![host_node];
+        seen.insert(host_node);
+        while let Some(current) = stack.pop() {
+            reached.extend(&host_components[&current]);
+            for neighbor in host
+                .neighbors_directed(current, Outgoing)
+                .chain(host.neighbors_directed(current, Incoming))
+            {
+                if host_components.contains_key(&neighbor) && seen.insert(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+        reached.len() == component_count
+    }
+
+    /// Check that a complete mapping is the canonical one among the mappings
+    /// that differ from it only by an automorphism of the motif.
+    ///
+    /// Applying an automorphism to a valid mapping always keeps the motif's
+    /// structure, but with node or edge predicates, the result may not be a
+    /// valid match itself; such mappings don't count.
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The candidate mapping, which does not contain `node`
+    /// * `node` - The last motif node to be mapped
+    /// * `host_node` - The host node that `node` would be mapped onto
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn is_canonical<G, H>(
+        candidate: &HashMap<G::NodeId, H::NodeId>,
+        node: G::NodeId,
+        host_node: H::NodeId,
+        motif: G,
+        host: H,
+        info: &InfoFor<G, H>,
+        options: &OptionsFor<G, H>,
+    ) -> bool
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let host_of = |motif_node: G::NodeId| {
+            if motif_node == node {
+                host_node
+            } else {
+                candidate[&motif_node]
+            }
+        };
+        info.automorphisms.iter().all(|automorphism| {
+            // Compare the mapping that first applies the automorphism with
+            // this one, in the motif's node order.
+            let comparison = info
+                .nodes
+                .iter()
+                .map(|motif_node| host_of(automorphism[motif_node]).cmp(&host_of(*motif_node)))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal);
+            if comparison != Ordering::Less {
+                return true;
+            }
+            // A smaller mapping only wins if it is a valid match, too.
+            let nodes_match = info.nodes.iter().all(|motif_node| {
+                is_structural_match(*motif_node, host_of(automorphism[motif_node]), options)
+            });
+            let edges_match = options.edge_match.is_none()
+                || motif.edge_references().all(|edge| {
+                    has_matching_edges(
+                        motif,
+                        (edge.source(), edge.target()),
+                        host,
+                        (
+                            host_of(automorphism[&edge.source()]),
+                            host_of(automorphism[&edge.target()]),
+                        ),
+                        info,
+                        options,
+                    )
+                });
+            !(nodes_match && edges_match)
+        })
+    }
+
+    /// Check the things about a complete mapping that can only be told once
+    /// every motif node is mapped: that the matched components are joined
+    /// together (if they must be), and that the mapping is the canonical one
+    /// (if only canonical mappings are searched for).
+    ///
+    /// # Arguments
+    ///
+    /// * `candidate` - The candidate mapping, which does not contain `node`
+    /// * `node` - The last motif node to be mapped
+    /// * `host_node` - The host node that `node` would be mapped onto
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `info` - The precomputed motif information
+    /// * `options` - The search configuration
+    ///
+    fn is_complete_match<G, H>(
+        candidate: &HashMap<G::NodeId, H::NodeId>,
+        node: G::NodeId,
+        host_node: H::NodeId,
+        motif: G,
+        host: H,
+        info: &InfoFor<G, H>,
+        options: &OptionsFor<G, H>,
+    ) -> bool
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let joined = info.component_count == 1
+            || options.host_connectivity != HostConnectivity::Connected
+            || joins_components::<G, H>(
+                candidate,
+                node,
+                host_node,
+                host,
+                &info.components,
+                info.component_count,
+            );
+        joined
+            && (info.automorphisms.is_empty()
+                || is_canonical(candidate, node, host_node, motif, host, info, options))
+    }
+
+    /// Identify all candidate subgraph monomorphisms between a motif and
+    /// a host graph.
+    ///
+    /// Both graphs can be any petgraph graph type that implements
+    /// `SearchGraph`, e.g. `&DiGraphMap`, `&Graph` or `&StableGraph`.
+    ///
+    /// The motif may have more than one connected component; each one is
+    /// matched onto its own host nodes.
+    ///
+    /// Returns an error if the motif is empty, or if only one of the two
+    /// graphs is directed.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    pub fn find_motifs<G, H>(motif: G, host: H) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        find_motifs_with_options(motif, host, &SearchOptions::default())
+    }
+
+    /// Identify all candidate mappings between a motif and a host graph,
+    /// using the given search configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    pub fn find_motifs_with_options<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        Ok(find_motifs_iter_with_options(motif, host, options)?.collect())
+    }
+
+    /// Count the subgraph monomorphisms between a motif and a host graph.
+    ///
+    /// This runs the same search as `find_motifs`, but complete mappings are
+    /// only counted, never built or stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    pub fn count_motifs<G, H>(motif: G, host: H) -> Result<usize, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        count_motifs_with_options(motif, host, &SearchOptions::default())
+    }
+
+    /// Count the mappings between a motif and a host graph, using the given
+    /// search configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    pub fn count_motifs_with_options<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<usize, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        Ok(find_motifs_iter_with_options(motif, host, options)?.count())
+    }
+
+    /// Identify candidate mappings between a motif and a host graph, and
+    /// report whether the search was cut short.
+    ///
+    /// This is `find_motifs_with_options`, for searches with a `deadline`
+    /// or a `cancel` flag: the mappings found until the search was stopped
+    /// are returned along with an `interrupted` flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    pub fn find_motifs_report<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<SearchReport<Vec<Mapping<G, H>>>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let mut iter = find_motifs_iter_with_options(motif, host, options)?;
+        let results = iter.by_ref().collect();
+        Ok(SearchReport {
+            results,
+            interrupted: iter.is_interrupted(),
+            stats: iter.stats,
+        })
+    }
+
+    /// Count the mappings between a motif and a host graph, and report
+    /// whether the search was cut short.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    pub fn count_motifs_report<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<SearchReport<usize>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let mut iter = find_motifs_iter_with_options(motif, host, options)?;
+        let results = iter.count_remaining();
+        Ok(SearchReport {
+            results,
+            interrupted: iter.is_interrupted(),
+            stats: iter.stats,
+        })
+    }
+
+    /// Count the automorphisms of a motif, i.e. the mappings of the motif
+    /// onto itself.
+    ///
+    /// Every distinct host subgraph that matches the motif is found once per
+    /// automorphism (an undirected triangle is found 6 times, a directed one
+    /// 3 times), so dividing `count_motifs` by this number gives the number
+    /// of distinct matching subgraphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    ///
+    pub fn count_automorphisms<G>(motif: G) -> Result<usize, GrandIsoError>
+    where
+        G: SearchGraph,
+    {
+        // A mapping of the motif onto itself is a bijection on its nodes
+        // that keeps every edge, i.e. an automorphism.
+        count_motifs(motif, motif)
+    }
+
+    /// Identify all homomorphisms from a motif into a host graph.
+    ///
+    /// Unlike `find_motifs`, several motif nodes may be mapped onto the same
+    /// host node. This is `find_motifs_with_options` with
+    /// `MatchMode::Homomorphism`.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    pub fn find_homomorphisms<G, H>(motif: G, host: H) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let options = SearchOptions::new().mode(MatchMode::Homomorphism);
+        find_motifs_with_options(motif, host, &options)
+    }
+
+    /// Count the homomorphisms from a motif into a host graph.
+    ///
+    /// This count includes every monomorphism, along with the mappings that
+    /// reuse host nodes, so it is at least as large as `count_motifs`.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    pub fn count_homomorphisms<G, H>(motif: G, host: H) -> Result<usize, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        let options = SearchOptions::new().mode(MatchMode::Homomorphism);
+        count_motifs_with_options(motif, host, &options)
+    }
+
+    /// Identify the automorphisms of a motif, i.e. the mappings of the
+    /// motif onto itself, including the identity.
+    ///
+    /// Node and edge weights are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    ///
+    pub fn find_automorphisms<G>(motif: G) -> Result<Vec<Mapping<G, G>>, GrandIsoError>
+    where
+        G: SearchGraph,
+    {
+        find_motifs(motif, motif)
+    }
+
+    /// Lazily identify candidate subgraph monomorphisms between a motif and
+    /// a host graph.
+    ///
+    /// Unlike `find_motifs`, the search only advances as far as is needed to
+    /// produce the next mapping, so callers can stop early (e.g. with
+    /// `take(n)`) or stream results without holding them all in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    pub fn find_motifs_iter<'a, G, H>(
+        motif: G,
+        host: H,
+    ) -> Result<MotifIter<'a, G, H>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        check_motif(motif, host)?;
+        Ok(MotifIter::new(
+            motif,
+            host,
+            MaybeOwned::Owned(SearchOptions::default()),
+            MatchMode::default(),
+        ))
+    }
+
+    /// Lazily identify candidate mappings between a motif and a host graph,
+    /// using the given search configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    pub fn find_motifs_iter_with_options<'a, G, H>(
+        motif: G,
+        host: H,
+        options: &'a OptionsFor<'a, G, H>,
+    ) -> Result<MotifIter<'a, G, H>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        check_motif(motif, host)?;
+        check_options::<G, H>(motif, options)?;
+        Ok(MotifIter::new(
+            motif,
+            host,
+            MaybeOwned::Borrowed(options),
+            options.mode,
+        ))
+    }
+
+    /// Check whether two graphs are isomorphic.
+    ///
+    /// This runs the motif search in `MatchMode::Isomorphism`, and stops at
+    /// the first isomorphism it finds. (Parallel edges are compared by
+    /// whether they exist, not by how many of them there are, although the
+    /// total numbers of edges must be the same.)
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first graph
+    /// * `b` - The second graph
+    ///
+    pub fn is_isomorphic<G, H>(a: G, b: H) -> Result<bool, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        is_isomorphic_with_options(a, b, &SearchOptions::default())
+    }
+
+    /// Check whether two graphs are isomorphic, using the given search
+    /// configuration, e.g. to also compare node or edge attributes.
+    ///
+    /// The `mode` and the `limit` of the options are ignored. If the search
+    /// is stopped by its deadline or cancellation flag before it finds an
+    /// isomorphism, the answer isn't known, and `GrandIsoError::Interrupted`
+    /// is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first graph
+    /// * `b` - The second graph
+    /// * `options` - The search configuration (e.g. the node predicate)
+    ///
+    pub fn is_isomorphic_with_options<G, H>(
+        a: G,
+        b: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<bool, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        check_options::<G, H>(a, options)?;
+        // The search can't start from an empty graph, but it doesn't need
+        // to: two empty graphs are isomorphic, and an empty graph isn't
+        // isomorphic to any other graph.
+        if a.node_count() == 0 || b.node_count() == 0 {
+            return Ok(a.node_count() == b.node_count());
+        }
+        check_motif(a, b)?;
+        let mut isomorphisms =
+            MotifIter::new(a, b, MaybeOwned::Borrowed(options), MatchMode::Isomorphism);
+        // One isomorphism is all it takes, so a limit can only get in the
+        // way (a limit of zero would make any two graphs look different).
+        isomorphisms.remaining = None;
+        let found = isomorphisms.next().is_some();
+        if !found && isomorphisms.is_interrupted() {
+            return Err(GrandIsoError::Interrupted);
+        }
+        Ok(found)
+    }
+
+    /// Identify all isomorphisms between two graphs.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first graph
+    /// * `b` - The second graph
+    ///
+    pub fn find_isomorphisms<G, H>(a: G, b: H) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        find_isomorphisms_with_options(a, b, &SearchOptions::default())
+    }
+
+    /// Identify all isomorphisms between two graphs, using the given search
+    /// configuration.
+    ///
+    /// The `mode` of the options is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - The first graph
+    /// * `b` - The second graph
+    /// * `options` - The search configuration (e.g. the node predicate)
+    ///
+    pub fn find_isomorphisms_with_options<G, H>(
+        a: G,
+        b: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        check_options::<G, H>(a, options)?;
+        // As in `is_isomorphic_with_options`, empty graphs aren't searched.
+        // Two of them have one isomorphism: the empty mapping.
+        if a.node_count() == 0 || b.node_count() == 0 {
+            let both_empty = a.node_count() == b.node_count();
+            return Ok(if both_empty && options.limit != Some(0) {
+                vec![HashMap::new()]
+            } else {
+                vec![]
+            });
+        }
+        check_motif(a, b)?;
+        Ok(MotifIter::new(a, b, MaybeOwned::Borrowed(options), MatchMode::Isomorphism).collect())
+    }
+
+    /// Identify all candidate subgraph monomorphisms between a motif and a
+    /// host graph, using every thread of the rayon thread pool.
+    ///
+    /// This finds the same mappings as `find_motifs`, but partial mappings
+    /// are grown in parallel. Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    #[cfg(feature = "parallel")]
+    pub fn par_find_motifs<G, H>(motif: G, host: H) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        par_find_motifs_with_options(motif, host, &SearchOptions::default())
+    }
+
+    /// Identify all candidate mappings between a motif and a host graph in
+    /// parallel, using the given search configuration.
+    ///
+    /// The mappings come back in no particular order, unless the options
+    /// ask for a `deterministic` search. (The `search_order` is ignored.)
+    /// Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    #[cfg(feature = "parallel")]
+    pub fn par_find_motifs_with_options<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<Vec<Mapping<G, H>>, GrandIsoError>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        Ok(par_find_motifs_report(motif, host, options)?.results)
+    }
+
+    /// Count the subgraph monomorphisms between a motif and a host graph,
+    /// using every thread of the rayon thread pool.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    ///
+    #[cfg(feature = "parallel")]
+    pub fn par_count_motifs<G, H>(motif: G, host: H) -> Result<usize, GrandIsoError>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        par_count_motifs_with_options(motif, host, &SearchOptions::default())
+    }
+
+    /// Count the mappings between a motif and a host graph in parallel,
+    /// using the given search configuration.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    #[cfg(feature = "parallel")]
+    pub fn par_count_motifs_with_options<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<usize, GrandIsoError>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        Ok(par_count_motifs_report(motif, host, options)?.results)
+    }
+
+    /// Identify candidate mappings between a motif and a host graph in
+    /// parallel, and report whether the search was cut short.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    #[cfg(feature = "parallel")]
+    pub fn par_find_motifs_report<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<SearchReport<Vec<Mapping<G, H>>>, GrandIsoError>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        Ok(find_motifs_iter_with_options(motif, host, options)?.par_collect())
+    }
+
+    /// Count the mappings between a motif and a host graph in parallel, and
+    /// report whether the search was cut short.
+    ///
+    /// Requires the `parallel` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `motif` - The motif graph network
+    /// * `host` - The host graph
+    /// * `options` - The search configuration (e.g. the `MatchMode`)
+    ///
+    #[cfg(feature = "parallel")]
+    pub fn par_count_motifs_report<G, H>(
+        motif: G,
+        host: H,
+        options: &OptionsFor<G, H>,
+    ) -> Result<SearchReport<usize>, GrandIsoError>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        Ok(find_motifs_iter_with_options(motif, host, options)?.par_count())
+    }
+
+    /// How many partial mappings are expanded between checks of a search's
+    /// deadline and cancellation flag.
+    const INTERRUPT_CHECK_INTERVAL: usize = 64;
+
+    /// A value that is either owned or borrowed from the caller.
+    enum MaybeOwned<'a, X> {
+        Owned(X),
+        Borrowed(&'a X),
+    }
+
+    impl<'a, X> Deref for MaybeOwned<'a, X> {
+        type Target = X;
+
+        fn deref(&self) -> &X {
+            match self {
+                MaybeOwned::Owned(value) => value,
+                MaybeOwned::Borrowed(value) => value,
+            }
+        }
+    }
+
+    /// Where a search is in seeding its queue from host edges.
+    struct Seeder<N, V> {
+        /// The motif edge that host edges are mapped onto
+        edge: (N, N),
+        /// The host nodes whose outgoing edges are still to be seeded
+        host_nodes: std::vec::IntoIter<V>,
+    }
+
+    /// An iterator over the mappings between a motif and a host graph.
+    ///
+    /// Created by `find_motifs_iter` and `find_motifs_iter_with_options`.
+    /// Each call to `next` seeds the search queue from host edges, or pops
+    /// partial mappings off of it and grows them, until at least one
+    /// complete mapping has been found.
+    pub struct MotifIter<'a, G, H>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        motif: G,
+        host: H,
+        options: MaybeOwned<'a, OptionsFor<'a, G, H>>,
+        info: InfoFor<G, H>,
+        seeder: Option<Seeder<G::NodeId, H::NodeId>>,
+        queue: VecDeque<HashMap<G::NodeId, H::NodeId>>,
+        results: VecDeque<HashMap<G::NodeId, H::NodeId>>,
+        counted: usize,
+        /// How many more mappings may be found before the limit is reached
+        remaining: Option<usize>,
+        /// How many partial mappings have been expanded so far
+        expanded: usize,
+        /// How many complete mappings have been found so far
+        found: usize,
+        interrupted: bool,
+        stats: Option<SearchStats>,
+    }
+
+    impl<'a, G, H> MotifIter<'a, G, H>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        fn new(
+            motif: G,
+            host: H,
+            options: MaybeOwned<'a, OptionsFor<'a, G, H>>,
+            mode: MatchMode,
+        ) -> Self {
+            // Generate a nodewise lookup (map) of interestingness, and
+            // everything else that is looked up about the motif.
+            let interestingness = score_interestingness(motif, host, &options);
+            let (components, component_count) = find_components(motif);
+            // Signatures assume that distinct motif neighbors are mapped onto
+            // distinct host neighbors, so they don't apply to homomorphisms.
+            let host_signatures = match mode {
+                MatchMode::Homomorphism => None,
+                _ => options.host_signatures,
+            };
+            let signatures = host_signatures.map(|host_signatures| {
+                let motif_labels = match &options.motif_labels {
+                    Some(motif_labels) if host_signatures.labeled => {
+                        Some(&**motif_labels as &dyn Fn(G::NodeId) -> usize)
+                    }
+                    _ => None,
+                };
+                graph_signatures(motif, motif_labels)
+            });
+            // The automorphisms are found with a search of the motif in
+            // itself, which can't fail if the search of the host doesn't.
+            let nodes: Vec<G::NodeId> = motif.node_identifiers().collect();
+            let mut forbidden_edges = HashSet::new();
+            for &(u, v) in &options.forbidden_edges {
+                forbidden_edges.insert((u, v));
+                if !motif.is_directed() {
+                    forbidden_edges.insert((v, u));
+                }
+            }
+            let mut automorphisms = vec![];
+            let mut constraints = HashMap::new();
+            if options.symmetry == Symmetry::Canonical {
+                // Only the automorphisms that keep the forbidden edges
+                // forbidden turn one valid mapping into another.
+                let all_automorphisms: Vec<_> = find_automorphisms(motif)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|automorphism: &HashMap<G::NodeId, G::NodeId>| {
+                        forbidden_edges.iter().all(|(u, v)| {
+                            forbidden_edges.contains(&(automorphism[u], automorphism[v]))
+                        })
+                    })
+                    .collect();
+                // The ordering constraints are strict, which only works if no
+                // two motif nodes share a host node.
+                if options.node_match.is_none()
+                    && options.edge_match.is_none()
+                    && mode != MatchMode::Homomorphism
+                {
+                    constraints = symmetry_constraints(&nodes, all_automorphisms);
+                } else {
+                    automorphisms = all_automorphisms
+                        .into_iter()
+                        .filter(|automorphism| automorphism.iter().any(|(u, v)| u != v))
+                        .collect();
+                }
+            }
+            // Edge weights, and host edges that mustn't be there, are looked up
+            // between any two host nodes, which would otherwise mean scanning
+            // the neighbors of one of them (a hub's, say) every time.
+            let looks_up_host_edges = options.edge_match.is_some()
+                || matches!(mode, MatchMode::Induced | MatchMode::Isomorphism)
+                || !forbidden_edges.is_empty()
+                || (options.host_connectivity == HostConnectivity::Disconnected
+                    && component_count > 1);
+            let host_edges = if looks_up_host_edges {
+                Some(index_host_edges(host))
+            } else {
+                None
+            };
+            let info = MotifInfo {
+                mode,
+                interestingness,
+                components,
+                component_count,
+                signatures,
+                nodes,
+                automorphisms,
+                symmetry_constraints: constraints,
+                forbidden_edges,
+                host_edges,
+            };
+
+            // Seed Q with the mappings of one motif edge onto host edges.
+            // Starting from edges rather than from single nodes keeps the
+            // queue growth down, as most pairs of host nodes aren't
+            // connected. The seeds are only made as the search needs them,
+            // one host node at a time. (Without any motif edges to start
+            // from, fall back to the empty mapping, which is grown one node
+            // at a time.)
+            let mut queue = VecDeque::new();
+            // Two graphs of different sizes can't be isomorphic, so there is
+            // no need to search at all.
+            let sizes_differ = mode == MatchMode::Isomorphism
+                && (motif.node_count() != host.node_count()
+                    || motif.edge_references().count() != host.edge_references().count());
+            let seeder = if sizes_differ {
+                None
+            } else if let Some(edge) = seed_edge(motif, &info) {
+                let host_nodes: Vec<H::NodeId> = host.node_identifiers().collect();
+                Some(Seeder {
+                    edge,
+                    host_nodes: host_nodes.into_iter(),
+                })
+            } else {
+                queue.push_back(HashMap::new());
+                None
+            };
+
+            let remaining = options.limit;
+            let stats = if options.collect_stats {
+                let mut stats = SearchStats::new(motif.node_count());
+                stats.peak_queue_len = queue.len();
+                Some(stats)
+            } else {
+                None
+            };
+            MotifIter {
+                motif,
+                host,
+                options,
+                info,
+                seeder,
+                queue,
+                results: VecDeque::new(),
+                counted: 0,
+                remaining,
+                expanded: 0,
+                found: 0,
+                interrupted: false,
+                stats,
+            }
+        }
+
+        /// Get the next motif node to map in a partial mapping, and all of
+        /// the host nodes that it can validly be mapped onto.
+        ///
+        /// This is `get_next_candidates`, plus the checks that can only be
+        /// made once the mapping is complete. Host nodes that fail those
+        /// count towards the ones rejected by the final edge check.
+        fn next_host_nodes(
+            &self,
+            candidate: &HashMap<G::NodeId, H::NodeId>,
+        ) -> (G::NodeId, Vec<H::NodeId>, usize) {
+            let (motif_node, mut host_nodes, mut rejected) =
+                get_next_candidates(candidate, self.motif, self.host, &self.info, &self.options);
+
+            if candidate.len() + 1 == self.motif.node_count()
+                && self.info.component_count > 1
+                && self.options.host_connectivity == HostConnectivity::Connected
+            {
+                // Whether the matched components are joined together can
+                // only be told once all of them have been matched.
+                let tentative_count = host_nodes.len();
+                host_nodes.retain(|host_node| {
+                    joins_components::<G, H>(
+                        candidate,
+                        motif_node,
+                        *host_node,
+                        self.host,
+                        &self.info.components,
+                        self.info.component_count,
+                    )
+                });
+                rejected += tentative_count - host_nodes.len();
+            }
+            if candidate.len() + 1 == self.motif.node_count() && !self.info.automorphisms.is_empty()
+            {
+                let tentative_count = host_nodes.len();
+                host_nodes.retain(|host_node| {
+                    is_canonical(
+                        candidate,
+                        motif_node,
+                        *host_node,
+                        self.motif,
+                        self.host,
+                        &self.info,
+                        &self.options,
+                    )
+                });
+                rejected += tentative_count - host_nodes.len();
+            }
+            (motif_node, host_nodes, rejected)
+        }
+
+        /// Check whether the search has been cut short, and remember it if
+        /// so.
+        ///
+        /// Checking the clock is cheap next to growing a partial mapping, but
+        /// not free, so it's only done every so often.
+        fn check_interrupted(&mut self) -> bool {
+            if !self.interrupted
+                && self.expanded % INTERRUPT_CHECK_INTERVAL == 0
+                && self.is_cut_short()
+            {
+                self.interrupted = true;
+            }
+            self.interrupted
+        }
+
+        /// Whether the search has passed its deadline or been cancelled.
+        fn is_cut_short(&self) -> bool {
+            self.options
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+                || self
+                    .options
+                    .cancel
+                    .as_ref()
+                    .is_some_and(|cancel| cancel.load(AtomicOrdering::Relaxed))
+        }
+
+        /// Whether `found` mappings are enough to reach the search's limit.
+        fn is_limit_reached(&self, found: usize) -> bool {
+            self.remaining.is_some_and(|remaining| found >= remaining)
+        }
+
+        /// Cut a number of found mappings down to the search's limit.
+        fn clamp_to_limit(&self, found: usize) -> usize {
+            self.remaining
+                .map_or(found, |remaining| found.min(remaining))
+        }
+
+        /// Grow the next partial mapping in the queue by one node, or seed
+        /// the queue from the next host node.
+        ///
+        /// Partial mappings are always added to the back of the queue; the
+        /// search order decides whether the next one is taken from the front
+        /// (breadth-first) or from the back (depth-first). New seeds are
+        /// made whenever a breadth-first search would expand them next, or a
+        /// depth-first search has nothing else left to expand.
+        ///
+        /// Returns false if there was nothing left to expand or seed, or if
+        /// the search was cut short.
+        fn expand(&mut self, count_only: bool) -> bool {
+            // A search with nothing left to do can't be cut short.
+            if self.queue.is_empty() && self.seeder.is_none() {
+                return false;
+            }
+            if self.check_interrupted() {
+                return false;
+            }
+            let depth_first = match self.options.search_order {
+                SearchOrder::BreadthFirst => false,
+                SearchOrder::DepthFirst => true,
+                SearchOrder::Hybrid(max_queue_len) => self.queue.len() > max_queue_len,
+            };
+            if self.seeder.is_some() && (self.queue.is_empty() || !depth_first) {
+                self.seed(count_only);
+                return true;
+            }
+            let next_candidate = if depth_first {
+                self.queue.pop_back()
+            } else {
+                self.queue.pop_front()
+            };
+            let candidate = match next_candidate {
+                Some(candidate) => candidate,
+                None => return false,
+            };
+            self.expanded += 1;
+
+            // Get the next motif node and all of the host nodes that it can
+            // validly be mapped onto:
+            let (motif_node, host_nodes, rejected) = self.next_host_nodes(&candidate);
+            self.add_mappings(&candidate, motif_node, &host_nodes, count_only);
+
+            if let Some(stats) = &mut self.stats {
+                stats.candidates[candidate.len()] += host_nodes.len();
+                stats.rejected[candidate.len()] += rejected;
+                stats.peak_queue_len = stats.peak_queue_len.max(self.queue.len());
+            }
+            self.report_progress(SearchProgress {
+                queue_len: self.queue.len(),
+                expanded: self.expanded,
+                found: self.found,
+                depth: candidate.len(),
+            });
+            true
+        }
+
+        /// Seed the queue with the mappings of the seed motif edge onto the
+        /// outgoing edges of the next host node.
+        ///
+        /// This counts as expanding the partial mapping of the seed edge's
+        /// source onto that host node.
+        fn seed(&mut self, count_only: bool) {
+            let seeder = match &mut self.seeder {
+                Some(seeder) => seeder,
+                None => return,
+            };
+            let (source, target) = seeder.edge;
+            let host_source = match seeder.host_nodes.next() {
+                Some(host_source) => host_source,
+                None => {
+                    self.seeder = None;
+                    return;
+                }
+            };
+            self.expanded += 1;
+
+            let (host_targets, rejected) = seed_targets(
+                (source, target),
+                host_source,
+                self.motif,
+                self.host,
+                &self.info,
+                &self.options,
+            );
+            let candidate = once((source, host_source)).collect();
+            self.add_mappings(&candidate, target, &host_targets, count_only);
+
+            if let Some(stats) = &mut self.stats {
+                stats.seeds += host_targets.len();
+                stats.candidates[1] += host_targets.len();
+                stats.rejected[1] += rejected;
+                stats.peak_queue_len = stats.peak_queue_len.max(self.queue.len());
+            }
+            self.report_progress(SearchProgress {
+                queue_len: self.queue.len(),
+                expanded: self.expanded,
+                found: self.found,
+                depth: 1,
+            });
+        }
+
+        /// Extend a partial mapping by mapping `motif_node` onto each of
+        /// `host_nodes`.
+        ///
+        /// New partial mappings are added back into the queue, and complete
+        /// mappings are held in `results` until they are requested. If
+        /// `count_only` is set, complete mappings are never built at all;
+        /// they're only tallied in `counted`.
+        fn add_mappings(
+            &mut self,
+            candidate: &HashMap<G::NodeId, H::NodeId>,
+            motif_node: G::NodeId,
+            host_nodes: &[H::NodeId],
+            count_only: bool,
+        ) {
+            let new_mappings = host_nodes.iter().map(|host_node| {
+                let mut mapping = candidate.clone();
+                mapping.insert(motif_node, *host_node);
+                mapping
+            });
+
+            if candidate.len() + 1 == self.motif.node_count() {
+                // Then each of these is a complete mapping; hold on to them
+                // until they are requested.
+                self.found += host_nodes.len();
+                if count_only {
+                    self.counted += host_nodes.len();
+                } else {
+                    self.results.extend(new_mappings);
+                }
+            } else {
+                // Otherwise, these are valid partial mappings, and they
+                // should be added back into the queue:
+                self.queue.extend(new_mappings);
+            }
+        }
+
+        /// Call the progress callback, if there is one and if it's due.
+        fn report_progress(&self, progress: SearchProgress) {
+            if let Some((every, callback)) = &self.options.progress {
+                if progress.expanded % *every == 0 {
+                    callback(&progress);
+                }
+            }
+        }
+    }
+
+    impl<'a, G, H> MotifIter<'a, G, H>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        /// Whether the search was cut short by its deadline or cancellation
+        /// flag (as opposed to having found every mapping, or having reached
+        /// its limit).
+        pub fn is_interrupted(&self) -> bool {
+            self.interrupted
+        }
+
+        /// Statistics about the search so far, if the options asked for them.
+        pub fn stats(&self) -> Option<&SearchStats> {
+            self.stats.as_ref()
+        }
+
+        /// Count the remaining mappings without building the complete ones.
+        fn count_remaining(&mut self) -> usize {
+            while !self.is_limit_reached(self.results.len() + self.counted) && self.expand(true) {}
+            let found = self.clamp_to_limit(self.results.len() + self.counted);
+            self.results.clear();
+            self.counted = 0;
+            if let Some(remaining) = &mut self.remaining {
+                *remaining -= found;
+            }
+            found
+        }
+    }
+
+    impl<'a, G, H> Iterator for MotifIter<'a, G, H>
+    where
+        G: SearchGraph,
+        H: SearchGraph,
+    {
+        type Item = HashMap<G::NodeId, H::NodeId>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.is_limit_reached(0) {
+                return None;
+            }
+            // Loop until we have a complete mapping to hand back, or until
+            // the queue is empty.
+            while self.results.is_empty() {
+                if !self.expand(false) {
+                    return None;
+                }
+            }
+            if let Some(remaining) = &mut self.remaining {
+                *remaining -= 1;
+            }
+            self.results.pop_front()
+        }
+
+        /// Count the remaining mappings without building the complete ones.
+        fn count(mut self) -> usize {
+            self.count_remaining()
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    impl<'a, G, H> MotifIter<'a, G, H>
+    where
+        G: SearchGraph + Sync,
+        H: SearchGraph + Sync,
+        G::NodeId: Send + Sync,
+        H::NodeId: Send + Sync,
+        H::EdgeRef: Sync,
+    {
+        /// Seed the queue from all of the remaining host nodes, unless the
+        /// search is cut short or reaches its limit first. Only growing the
+        /// seeds is done in parallel.
+        fn seed_all(&mut self, count_only: bool) {
+            while self.seeder.is_some()
+                && !self.is_limit_reached(self.results.len() + self.counted)
+                && !self.check_interrupted()
+            {
+                self.seed(count_only);
+            }
+        }
+
+        /// The shared counters for the tasks of a parallel search, picking
+        /// up where seeding left off.
+        fn par_state(&self, pending: usize) -> ParState {
+            let motif_size = self.motif.node_count();
+            ParState {
+                pending: AtomicUsize::new(pending),
+                expanded: AtomicUsize::new(self.expanded),
+                found: AtomicUsize::new(self.results.len() + self.counted),
+                interrupted: AtomicBool::new(self.interrupted),
+                candidates: (0..motif_size).map(|_| AtomicUsize::new(0)).collect(),
+                rejected: (0..motif_size).map(|_| AtomicUsize::new(0)).collect(),
+                peak_pending: AtomicUsize::new(pending),
+            }
+        }
+
+        /// Whether the parallel search should stop, because it has been
+        /// cut short. Once one task has noticed, `state.interrupted` tells
+        /// the others without checking the clock again.
+        fn par_is_cut_short(&self, state: &ParState) -> bool {
+            if state.interrupted.load(AtomicOrdering::Relaxed) {
+                return true;
+            }
+            let cut_short = self.is_cut_short();
+            if cut_short {
+                state.interrupted.store(true, AtomicOrdering::Relaxed);
+            }
+            cut_short
+        }
+
+        /// Get the next motif node and its host nodes for a partial mapping,
+        /// like `next_host_nodes`, and keep track of the search's progress.
+        fn par_next_host_nodes(
+            &self,
+            candidate: &HashMap<G::NodeId, H::NodeId>,
+            state: &ParState,
+        ) -> (G::NodeId, Vec<H::NodeId>) {
+            let (motif_node, host_nodes, rejected) = self.next_host_nodes(candidate);
+            let complete = candidate.len() + 1 == self.motif.node_count();
+            let (found, queue_len) = if complete {
+                (
+                    state
+                        .found
+                        .fetch_add(host_nodes.len(), AtomicOrdering::Relaxed)
+                        + host_nodes.len(),
+                    state.pending.fetch_sub(1, AtomicOrdering::Relaxed) - 1,
+                )
+            } else {
+                (
+                    state.found.load(AtomicOrdering::Relaxed),
+                    state
+                        .pending
+                        .fetch_add(host_nodes.len(), AtomicOrdering::Relaxed)
+                        - 1
+                        + host_nodes.len(),
+                )
+            };
+            if self.stats.is_some() {
+                let depth = candidate.len();
+                state.candidates[depth].fetch_add(host_nodes.len(), AtomicOrdering::Relaxed);
+                state.rejected[depth].fetch_add(rejected, AtomicOrdering::Relaxed);
+                state
+                    .peak_pending
+                    .fetch_max(queue_len, AtomicOrdering::Relaxed);
+            }
+            // Other threads may have moved on in the meantime, so the
+            // numbers are only a snapshot.
+            self.report_progress(SearchProgress {
+                queue_len,
+                expanded: state.expanded.fetch_add(1, AtomicOrdering::Relaxed) + 1,
+                found,
+                depth: candidate.len(),
+            });
+            (motif_node, host_nodes)
+        }
+
+        /// Add the statistics that the tasks of a parallel search collected
+        /// to the ones from setting up the search.
+        fn par_stats(&self, state: ParState) -> Option<SearchStats> {
+            let mut stats = self.stats.clone()?;
+            for (total, count) in stats.candidates.iter_mut().zip(state.candidates) {
+                *total += count.into_inner();
+            }
+            for (total, count) in stats.rejected.iter_mut().zip(state.rejected) {
+                *total += count.into_inner();
+            }
+            stats.peak_queue_len = stats.peak_queue_len.max(state.peak_pending.into_inner());
+            Some(stats)
+        }
+
+        /// Drain the search in parallel, collecting all of the remaining
+        /// mappings.
+        ///
+        /// Every partial mapping in the queue is grown to completion as its
+        /// own rayon task, and each one of those splits up into more tasks
+        /// as it grows, so idle threads can steal work from busy ones.
+        fn par_collect(mut self) -> SearchReport<Vec<HashMap<G::NodeId, H::NodeId>>> {
+            self.seed_all(false);
+            let queue = std::mem::take(&mut self.queue);
+            let mut results: Vec<_> = self.results.drain(..).collect();
+            let state = self.par_state(queue.len());
+            if self.options.deterministic && self.remaining.is_some() {
+                // To find the same first mappings as a serial search would,
+                // grow the queue a few entries at a time, in order, until
+                // there are enough of them.
+                let mut queue = Vec::from(queue);
+                for chunk in queue.chunks_mut(rayon::current_num_threads()) {
+                    if self.is_limit_reached(results.len()) {
+                        break;
+                    }
+                    results.par_extend(chunk.par_iter_mut().flat_map(|candidate| {
+                        self.par_grow_ordered(std::mem::take(candidate), &state)
+                    }));
+                }
+            } else if self.options.deterministic {
+                // Rayon collects in the order of the queue, no matter in which
+                // order the tasks finish.
+                results.par_extend(
+                    queue
+                        .into_par_iter()
+                        .flat_map(|candidate| self.par_grow_ordered(candidate, &state)),
+                );
+            } else {
+                let locked_results = Mutex::new(results);
+                queue
+                    .into_par_iter()
+                    .for_each(|candidate| self.par_grow(candidate, &locked_results, &state));
+                results = locked_results.into_inner().unwrap();
+            }
+            results.truncate(self.clamp_to_limit(results.len()));
+            SearchReport {
+                results,
+                interrupted: state.interrupted.load(AtomicOrdering::Relaxed),
+                stats: self.par_stats(state),
+            }
+        }
+
+        /// Grow a partial mapping into all of its complete mappings, in the
+        /// order in which a breadth-first search would find them.
+        fn par_grow_ordered(
+            &self,
+            candidate: HashMap<G::NodeId, H::NodeId>,
+            state: &ParState,
+        ) -> Vec<HashMap<G::NodeId, H::NodeId>> {
+            if self.par_is_cut_short(state) {
+                return vec![];
+            }
+            let (motif_node, host_nodes) = self.par_next_host_nodes(&candidate, state);
+            let complete = candidate.len() + 1 == self.motif.node_count();
+            host_nodes
+                .into_par_iter()
+                .flat_map(|host_node| {
+                    let mut mapping = candidate.clone();
+                    mapping.insert(motif_node, host_node);
+                    if complete {
+                        vec![mapping]
+                    } else {
+                        self.par_grow_ordered(mapping, state)
+                    }
+                })
+                .collect()
+        }
+
+        /// Grow a partial mapping into all of its complete mappings, and
+        /// add them to `results` as soon as they are found.
+        fn par_grow(
+            &self,
+            candidate: HashMap<G::NodeId, H::NodeId>,
+            results: &Mutex<Vec<HashMap<G::NodeId, H::NodeId>>>,
+            state: &ParState,
+        ) {
+            if self.is_limit_reached(state.found.load(AtomicOrdering::Relaxed))
+                || self.par_is_cut_short(state)
+            {
+                return;
+            }
+            let (motif_node, host_nodes) = self.par_next_host_nodes(&candidate, state);
+            let new_mappings = host_nodes.into_par_iter().map(|host_node| {
+                let mut mapping = candidate.clone();
+                mapping.insert(motif_node, host_node);
+                mapping
+            });
+            if candidate.len() + 1 == self.motif.node_count() {
+                let new_mappings: Vec<_> = new_mappings.collect();
+                results.lock().unwrap().extend(new_mappings);
+            } else {
+                new_mappings.for_each(|mapping| self.par_grow(mapping, results, state));
+            }
+        }
+
+        /// Count the remaining mappings in parallel, without building the
+        /// complete ones.
+        fn par_count(mut self) -> SearchReport<usize> {
+            self.seed_all(true);
+            let queue = std::mem::take(&mut self.queue);
+            let state = self.par_state(queue.len());
+            queue
+                .into_par_iter()
+                .for_each(|candidate| self.par_count_from(candidate, &state));
+            SearchReport {
+                results: self.clamp_to_limit(state.found.load(AtomicOrdering::Relaxed)),
+                interrupted: state.interrupted.load(AtomicOrdering::Relaxed),
+                stats: self.par_stats(state),
+            }
+        }
+
+        /// Count the complete mappings that a partial mapping grows into,
+        /// adding them to `state.found`.
+        fn par_count_from(&self, candidate: HashMap<G::NodeId, H::NodeId>, state: &ParState) {
+            if self.is_limit_reached(state.found.load(AtomicOrdering::Relaxed))
+                || self.par_is_cut_short(state)
+            {
+                return;
+            }
+            let (motif_node, host_nodes) = self.par_next_host_nodes(&candidate, state);
+            if candidate.len() + 1 == self.motif.node_count() {
+                return;
+            }
+            host_nodes.into_par_iter().for_each(|host_node| {
+                let mut mapping = candidate.clone();
+                mapping.insert(motif_node, host_node);
+                self.par_count_from(mapping, state)
+            });
+        }
+    }
+
+    /// The counters that the tasks of a parallel search share.
+    #[cfg(feature = "parallel")]
+    struct ParState {
+        /// How many partial mappings are waiting to be expanded
+        pending: AtomicUsize,
+        /// How many partial mappings have been expanded so far
+        expanded: AtomicUsize,
+        /// How many complete mappings have been found so far
+        found: AtomicUsize,
+        /// Whether the search has been cut short
+        interrupted: AtomicBool,
+        /// The number of host nodes that partial mappings of each depth
+        /// could be grown with, if statistics are collected
+        candidates: Vec<AtomicUsize>,
+        /// The number of host nodes that the final edge check rejected at
+        /// each depth, if statistics are collected
+        rejected: Vec<AtomicUsize>,
+        /// The largest number of partial mappings waiting at once, if
+        /// statistics are collected
+        peak_pending: AtomicUsize,
+    }
+}
+#[cfg(test)]
+mod tests {
+
+    use crate::grandiso;
+    use petgraph::csr::Csr;
+    use petgraph::graph::{Graph, NodeIndex};
+    use petgraph::graphmap::{DiGraphMap, UnGraphMap};
+    use petgraph::stable_graph::StableGraph;
+    use petgraph::Undirected;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_single_node() {
+        let mut graphmap: DiGraphMap<&str, &str> = DiGraphMap::new();
+        graphmap.add_node("A");
+
+        assert_eq!(
+            grandiso::find_motifs(&graphmap, &graphmap).unwrap().len(),
+            1
+        )
+    }
+    #[test]
+    fn test_single_edge() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 2);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 2);
+
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 1)
+    }
+
+    #[test]
+    fn test_single_edge_with_reverse_name() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 2);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(1, 0, 1);
+
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 1)
+    }
+
+    #[test]
+    fn test_one_motif_edge_with_two_edges() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 2);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 2);
+        host.add_edge(1, 0, 2);
+
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 2)
+    }
+
+    #[test]
+    fn test_two_edges() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 2);
+        graphmap.add_edge(1, 2, 3);
+
+        assert_eq!(
+            grandiso::find_motifs(&graphmap, &graphmap).unwrap().len(),
+            1
+        )
+    }
+
+    #[test]
+    fn test_directed_triangles() {
+        let mut graphmap: DiGraphMap<i8, &str> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, "3");
+        graphmap.add_edge(1, 2, "3");
+        graphmap.add_edge(2, 0, "3");
+        let mut graphmap_host: DiGraphMap<&str, &str> = DiGraphMap::new();
+        graphmap_host.add_edge("A", "B", "3");
+        graphmap_host.add_edge("B", "C", "3");
+        graphmap_host.add_edge("C", "A", "3");
+
+        let results = grandiso::find_motifs(&graphmap, &graphmap_host).unwrap();
+
+        // One mapping per rotation of the triangle:
+        assert_eq!(results.len(), 3)
+    }
+
+    #[test]
+    fn test_directed_triangles_fully_connected() {
+        let mut graphmap: DiGraphMap<&str, &str> = DiGraphMap::new();
+        graphmap.add_edge("0", "1", "3");
+        graphmap.add_edge("1", "2", "3");
+        graphmap.add_edge("2", "0", "3");
+        let mut graphmap_host: DiGraphMap<&str, &str> = DiGraphMap::new();
+        graphmap_host.add_edge("A", "B", "3");
+        graphmap_host.add_edge("B", "A", "3");
+        graphmap_host.add_edge("B", "C", "3");
+        graphmap_host.add_edge("C", "B", "3");
+        graphmap_host.add_edge("C", "A", "3");
+        graphmap_host.add_edge("A", "C", "3");
+
+        let results = grandiso::find_motifs(&graphmap, &graphmap_host).unwrap();
+
+        // Each of the 3! orderings of A, B, C is a valid mapping:
+        assert_eq!(results.len(), 6)
+    }
+
+    /// Build a complete directed graph on `n` nodes (edges in both directions).
+    fn complete_digraph(n: i8) -> DiGraphMap<i8, i8> {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        for u in 0..n {
+            for v in 0..n {
+                if u != v {
+                    graphmap.add_edge(u, v, 1);
+                }
+            }
+        }
+        graphmap
+    }
+
+    /// Flatten a mapping into a sorted list of (motif, host) pairs, so that
+    /// mappings can be compared and hashed.
+    fn sorted_pairs(mapping: &HashMap<i8, i8>) -> Vec<(i8, i8)> {
+        let mut pairs: Vec<(i8, i8)> = mapping.iter().map(|(k, v)| (*k, *v)).collect();
+        pairs.sort();
+        pairs
+    }
+
+    #[test]
+    fn test_no_reused_host_nodes() {
+        // A path of length two can only map onto the two-cycle by sending
+        // both endpoints to the same host node, which is not a monomorphism.
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(1, 2, 1);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 0, 1);
+
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 0)
+    }
+
+    #[test]
+    fn test_mappings_are_injective() {
+        let graphmap = complete_digraph(3);
+        let host = complete_digraph(5);
+
+        let results = grandiso::find_motifs(&graphmap, &host).unwrap();
+        assert!(results.iter().all(|mapping| {
+            let host_nodes: HashSet<&i8> = mapping.values().collect();
+            host_nodes.len() == mapping.len()
+        }));
+    }
+
+    #[test]
+    fn test_mappings_are_unique() {
+        let graphmap = complete_digraph(3);
+        let host = complete_digraph(4);
+
+        let results = grandiso::find_motifs(&graphmap, &host).unwrap();
+        let unique: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        assert_eq!(unique.len(), results.len());
+    }
+
+    #[test]
+    fn test_paths() {
+        // 0 -> 1 -> 2 in the path A -> B -> C -> D: ABC and BCD.
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(1, 2, 1);
+
+        let mut host: DiGraphMap<&str, i8> = DiGraphMap::new();
+        host.add_edge("A", "B", 1);
+        host.add_edge("B", "C", 1);
+        host.add_edge("C", "D", 1);
+
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 2);
+
+        // In K4 every ordered triple of distinct nodes is a path: 4 * 3 * 2.
+        assert_eq!(
+            grandiso::find_motifs(&graphmap, &complete_digraph(4))
+                .unwrap()
+                .len(),
+            24
+        );
+    }
+
+    #[test]
+    fn test_stars() {
+        // An out-star with three leaves:
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(0, 2, 1);
+        graphmap.add_edge(0, 3, 1);
+
+        // A host out-star with four leaves: 4 * 3 * 2 ordered leaf choices.
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        for leaf in 1..5 {
+            host.add_edge(0, leaf, 1);
+        }
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 24);
+
+        // Flipping the host edges leaves no node with three out-neighbors.
+        let mut reversed: DiGraphMap<i8, i8> = DiGraphMap::new();
+        for leaf in 1..5 {
+            reversed.add_edge(leaf, 0, 1);
+        }
+        assert_eq!(
+            grandiso::find_motifs(&graphmap, &reversed).unwrap().len(),
+            0
+        );
+
+        // A star with only two leaves can't fit three distinct motif leaves.
+        let mut small: DiGraphMap<i8, i8> = DiGraphMap::new();
+        small.add_edge(0, 1, 1);
+        small.add_edge(0, 2, 1);
+        assert_eq!(grandiso::find_motifs(&graphmap, &small).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_cliques() {
+        // K_m in K_n has n! / (n - m)! mappings.
+        assert_eq!(
+            grandiso::find_motifs(&complete_digraph(3), &complete_digraph(3))
+                .unwrap()
+                .len(),
+            6
+        );
+        assert_eq!(
+            grandiso::find_motifs(&complete_digraph(3), &complete_digraph(5))
+                .unwrap()
+                .len(),
+            60
+        );
+        assert_eq!(
+            grandiso::find_motifs(&complete_digraph(4), &complete_digraph(5))
+                .unwrap()
+                .len(),
+            120
+        );
+        assert_eq!(
+            grandiso::find_motifs(&complete_digraph(4), &complete_digraph(3))
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_induced_rejects_extra_edges() {
+        // A single motif edge maps onto either direction of a two-cycle, but
+        // the reverse host edge makes neither an induced match.
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 0, 1);
+
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 2);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&graphmap, &host, &induced)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_induced_paths() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(1, 2, 1);
+
+        // A -> B -> C -> D, plus the chord D -> B which closes B, C, D into
+        // a triangle. ABC, BCD, CDB and DBC are all paths, but only
+        // A -> B -> C is an induced one.
+        let mut host: DiGraphMap<&str, i8> = DiGraphMap::new();
+        host.add_edge("A", "B", 1);
+        host.add_edge("B", "C", 1);
+        host.add_edge("C", "D", 1);
+        host.add_edge("D", "B", 1);
+
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 4);
+        let results = grandiso::find_motifs_with_options(&graphmap, &host, &induced).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].get(&0), Some(&"A"));
+
+        // No two-path in a clique is induced.
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&graphmap, &complete_digraph(4), &induced)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_induced_cliques() {
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(
+            grandiso::find_motifs_with_options(
+                &complete_digraph(3),
+                &complete_digraph(5),
+                &induced
+            )
+            .unwrap()
+            .len(),
+            60
+        );
+    }
+
+    #[test]
+    fn test_induced_self_loops() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 1, 1);
+
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 1);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&graphmap, &host, &induced)
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_node_match_labels() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        let mut motif_labels = HashMap::new();
+        motif_labels.insert(0, "excitatory");
+        motif_labels.insert(1, "inhibitory");
+
+        let host = complete_digraph(4);
+        let mut host_labels = HashMap::new();
+        host_labels.insert(0, "excitatory");
+        host_labels.insert(1, "excitatory");
+        host_labels.insert(2, "inhibitory");
+        host_labels.insert(3, "inhibitory");
+
+        let options = grandiso::SearchOptions::new().node_match(|motif_node, host_node| {
+            motif_labels[&motif_node] == host_labels[&host_node]
+        });
+        let results = grandiso::find_motifs_with_options(&graphmap, &host, &options).unwrap();
+
+        // Two excitatory sources times two inhibitory targets:
+        assert_eq!(results.len(), 4);
+        assert!(results
+            .iter()
+            .all(|mapping| mapping[&0] < 2 && mapping[&1] >= 2));
+    }
+
+    #[test]
+    fn test_node_match_seed() {
+        // A single-node motif never grows past the seed, so the predicate
+        // must be applied when the seeds are created.
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_node(0);
+
+        let options =
+            grandiso::SearchOptions::new().node_match(|_, host_node: i8| host_node % 2 == 0);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&graphmap, &complete_digraph(5), &options)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_node_match_rejects_everything() {
+        let options = grandiso::SearchOptions::new().node_match(|_, _| false);
+        assert_eq!(
+            grandiso::find_motifs_with_options(
+                &complete_digraph(3),
+                &complete_digraph(4),
+                &options
+            )
+            .unwrap()
+            .len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_edge_match_equal_weights() {
+        let mut graphmap: DiGraphMap<i8, &str> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, "chemical");
+        graphmap.add_edge(1, 2, "electrical");
+
+        let mut host: DiGraphMap<&str, &str> = DiGraphMap::new();
+        host.add_edge("A", "B", "chemical");
+        host.add_edge("B", "C", "electrical");
+        host.add_edge("C", "D", "chemical");
+        host.add_edge("D", "E", "chemical");
+
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 3);
+
+        let options = grandiso::SearchOptions::new().edge_match(|a, b| a == b);
+        let results = grandiso::find_motifs_with_options(&graphmap, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0][&0], "A");
+    }
+
+    #[test]
+    fn test_edge_match_non_node_weights() {
+        // Edge weights don't have to be usable as node identifiers:
+        let mut graphmap: DiGraphMap<i8, f32> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 2.0);
+        graphmap.add_edge(1, 2, 2.0);
+        graphmap.add_edge(2, 0, 2.0);
+
+        let mut host: DiGraphMap<i8, f32> = DiGraphMap::new();
+        host.add_edge(0, 1, 5.0);
+        host.add_edge(1, 2, 3.5);
+        host.add_edge(2, 0, 1.0);
+
+        // Require at least as many synapses in the host as in the motif:
+        let options = grandiso::SearchOptions::new().edge_match(|m: &f32, h: &f32| h >= m);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&graphmap, &host, &options)
+                .unwrap()
+                .len(),
+            0
+        );
+
+        // Strengthening the weakest edge makes every rotation a match:
+        host.add_edge(2, 0, 2.5);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&graphmap, &host, &options)
+                .unwrap()
+                .len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_edge_match_self_loops() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 0, 7);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 0, 1);
+        host.add_edge(1, 1, 7);
+
+        let options = grandiso::SearchOptions::new().edge_match(|a, b| a == b);
+        let results = grandiso::find_motifs_with_options(&graphmap, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0][&0], 1);
+    }
+
+    #[test]
+    fn test_iter_matches_find_motifs() {
+        let graphmap = complete_digraph(3);
+        let host = complete_digraph(5);
+
+        let mut expected: Vec<Vec<(i8, i8)>> = grandiso::find_motifs(&graphmap, &host)
+            .unwrap()
+            .iter()
+            .map(sorted_pairs)
+            .collect();
+        let mut actual: Vec<Vec<(i8, i8)>> = grandiso::find_motifs_iter(&graphmap, &host)
+            .unwrap()
+            .map(|mapping| sorted_pairs(&mapping))
+            .collect();
+        expected.sort();
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_iter_stops_early() {
+        let graphmap = complete_digraph(3);
+        let host = complete_digraph(12);
+
+        // Count how many host nodes get considered for a motif node; taking
+        // a handful of results must not explore the whole state space.
+        let checks = AtomicUsize::new(0);
+        let options = grandiso::SearchOptions::new().node_match(|_, _| {
+            checks.fetch_add(1, Ordering::Relaxed);
+            true
+        });
+        let first: Vec<HashMap<i8, i8>> =
+            grandiso::find_motifs_iter_with_options(&graphmap, &host, &options)
+                .unwrap()
+                .take(5)
+                .collect();
+        assert_eq!(first.len(), 5);
+        let partial_checks = checks.load(Ordering::Relaxed);
+
+        checks.store(0, Ordering::Relaxed);
+        assert_eq!(
+            grandiso::find_motifs_iter_with_options(&graphmap, &host, &options)
+                .unwrap()
+                .count(),
+            12 * 11 * 10
+        );
+        assert!(partial_checks < checks.load(Ordering::Relaxed));
+
+        // Seeds are only made as they are needed, too: the first match of a
+        // single edge only looks at the first host node and its neighbors.
+        checks.store(0, Ordering::Relaxed);
+        let edge = complete_digraph(2);
+        let first = grandiso::find_motifs_iter_with_options(&edge, &host, &options)
+            .unwrap()
+            .next();
+        assert!(first.is_some());
+        assert_eq!(checks.load(Ordering::Relaxed), 12);
+    }
+
+    #[test]
+    fn test_iter_with_options() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(1, 2, 1);
+
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        let host = complete_digraph(4);
+        assert_eq!(
+            grandiso::find_motifs_iter_with_options(&graphmap, &host, &induced)
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_count_motifs() {
+        let mut path: DiGraphMap<i8, i8> = DiGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+
+        for (motif, host) in [
+            (path.clone(), complete_digraph(4)),
+            (complete_digraph(3), complete_digraph(6)),
+            (complete_digraph(4), complete_digraph(3)),
+        ] {
+            assert_eq!(
+                grandiso::count_motifs(&motif, &host).unwrap(),
+                grandiso::find_motifs(&motif, &host).unwrap().len()
+            );
+        }
+
+        assert_eq!(
+            grandiso::count_motifs(&complete_digraph(3), &complete_digraph(10)).unwrap(),
+            720
+        );
+    }
+
+    #[test]
+    fn test_count_motifs_single_node() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_node(0);
+        assert_eq!(
+            grandiso::count_motifs(&graphmap, &complete_digraph(7)).unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_count_motifs_with_options() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(1, 2, 1);
+
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&graphmap, &complete_digraph(5), &induced).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_reuse_borrowed_host() {
+        // One host can be queried with many motifs without being copied, and
+        // is still usable afterwards.
+        let host = complete_digraph(5);
+        let counts: Vec<usize> = (2..5)
+            .map(|n| grandiso::count_motifs(&complete_digraph(n), &host).unwrap())
+            .collect();
+        assert_eq!(counts, vec![20, 60, 120]);
+        assert_eq!(host.node_count(), 5);
+    }
+
+    #[test]
+    fn test_self_loops() {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 0, 1);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 0, 1);
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 0);
+
+        host.add_edge(1, 1, 1);
+        assert_eq!(grandiso::find_motifs(&graphmap, &host).unwrap().len(), 1);
+    }
+
+    fn directed_triangle() -> DiGraphMap<i8, i8> {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(1, 2, 1);
+        graphmap.add_edge(2, 0, 1);
+        graphmap
+    }
+
+    #[test]
+    fn test_graph_host() {
+        let mut host: Graph<(), i8> = Graph::new();
+        let nodes: Vec<NodeIndex> = (0..4).map(|_| host.add_node(())).collect();
+        for &u in &nodes {
+            for &v in &nodes {
+                if u != v {
+                    host.add_edge(u, v, 1);
+                }
+            }
+        }
+
+        // 4 triangles, each found 3 times per direction.
+        let results = grandiso::find_motifs(&directed_triangle(), &host).unwrap();
+        assert_eq!(results.len(), 24);
+        for mapping in &results {
+            let values: HashSet<&NodeIndex> = mapping.values().collect();
+            assert_eq!(values.len(), 3);
+        }
+
+        // A Graph can be the motif, too.
+        let mut motif: Graph<(), i8> = Graph::new();
+        let a = motif.add_node(());
+        let b = motif.add_node(());
+        motif.add_edge(a, b, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 12);
+    }
+
+    #[test]
+    fn test_graph_parallel_edges() {
+        // Parallel edges are required once, and any one of them may satisfy
+        // the edge predicate.
+        let mut motif: Graph<(), i8> = Graph::new();
+        let a = motif.add_node(());
+        let b = motif.add_node(());
+        motif.add_edge(a, b, 2);
+        motif.add_edge(a, b, 2);
+
+        let mut host: Graph<(), i8> = Graph::new();
+        let x = host.add_node(());
+        let y = host.add_node(());
+        let z = host.add_node(());
+        host.add_edge(x, y, 1);
+        host.add_edge(x, y, 2);
+        host.add_edge(y, z, 1);
+
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 2);
+        let options = grandiso::SearchOptions::new().edge_match(|m: &i8, h: &i8| m == h);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0][&a], x);
+        assert_eq!(results[0][&b], y);
+    }
+
+    #[test]
+    fn test_stable_graph_host() {
+        let mut host: StableGraph<(), i8> = StableGraph::new();
+        let nodes: Vec<NodeIndex> = (0..5).map(|_| host.add_node(())).collect();
+        for &u in &nodes {
+            for &v in &nodes {
+                if u != v {
+                    host.add_edge(u, v, 1);
+                }
+            }
+        }
+        assert_eq!(
+            grandiso::count_motifs(&directed_triangle(), &host).unwrap(),
+            60
+        );
+
+        // Removed nodes leave holes in the index space, which must never be
+        // mapped onto.
+        host.remove_node(nodes[2]);
+        let results = grandiso::find_motifs(&directed_triangle(), &host).unwrap();
+        assert_eq!(results.len(), 24);
+        for mapping in &results {
+            assert!(mapping.values().all(|n| *n != nodes[2]));
+        }
+    }
+
+    #[test]
+    fn test_csr_host() {
+        let csr: Csr<(), i8> =
+            Csr::from_sorted_edges(&[(0, 1, 1), (1, 2, 1), (1, 3, 2), (2, 0, 1), (3, 0, 1)])
+                .unwrap();
+        let host = grandiso::Bidirectional::new(&csr);
+
+        let results = grandiso::find_motifs(&directed_triangle(), &host).unwrap();
+        assert_eq!(results.len(), 6);
+
+        let options = grandiso::SearchOptions::new().edge_match(|m: &i8, h: &i8| m == h);
+        let results =
+            grandiso::find_motifs_with_options(&directed_triangle(), &host, &options).unwrap();
+        let mut results: Vec<Vec<(i8, u32)>> = results
+            .iter()
+            .map(|mapping| {
+                let mut pairs: Vec<(i8, u32)> = mapping.iter().map(|(k, v)| (*k, *v)).collect();
+                pairs.sort_unstable();
+                pairs
+            })
+            .collect();
+        results.sort();
+        assert_eq!(
+            results,
+            vec![
+                vec![(0, 0), (1, 1), (2, 2)],
+                vec![(0, 1), (1, 2), (2, 0)],
+                vec![(0, 2), (1, 0), (2, 1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undirected_triangles() {
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(2, 0, 1);
+
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for u in 0..4 {
+            for v in (u + 1)..4 {
+                host.add_edge(u, v, 1);
+            }
+        }
+
+        // 4 triangles in K4, each found once per automorphism (3 rotations
+        // times 2 reflections).
+        assert_eq!(grandiso::count_automorphisms(&motif).unwrap(), 6);
+        let results = grandiso::find_motifs(&motif, &host).unwrap();
+        assert_eq!(results.len(), 24);
+        let unique: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        assert_eq!(unique.len(), 24);
+        let subgraphs: HashSet<Vec<i8>> = results
+            .iter()
+            .map(|mapping| {
+                let mut nodes: Vec<i8> = mapping.values().cloned().collect();
+                nodes.sort_unstable();
+                nodes
+            })
+            .collect();
+        assert_eq!(subgraphs.len(), 4);
+    }
+
+    #[test]
+    fn test_undirected_paths() {
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        assert_eq!(grandiso::count_automorphisms(&motif).unwrap(), 2);
+
+        // A 4-node path contains 2 distinct 3-node paths, and a 4-node
+        // cycle contains 4. The cycle's paths aren't induced, though.
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 2, 1);
+        host.add_edge(2, 3, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 4);
+
+        host.add_edge(3, 0, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 8);
+        let options = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            8
+        );
+
+        host.add_edge(0, 2, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 16);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_undirected_graph_edges() {
+        // In an undirected Graph, the host edge may be stored in either
+        // orientation, and self-loops must still be required.
+        let mut motif: Graph<(), i8, Undirected> = Graph::new_undirected();
+        let a = motif.add_node(());
+        let b = motif.add_node(());
+        motif.add_edge(a, b, 2);
+        motif.add_edge(b, b, 3);
+
+        let mut host: Graph<(), i8, Undirected> = Graph::new_undirected();
+        let x = host.add_node(());
+        let y = host.add_node(());
+        let z = host.add_node(());
+        host.add_edge(y, x, 2);
+        host.add_edge(z, y, 2);
+        host.add_edge(x, x, 3);
+        host.add_edge(z, z, 4);
+
+        let options = grandiso::SearchOptions::new().edge_match(|m: &i8, h: &i8| m == h);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0][&a], y);
+        assert_eq!(results[0][&b], x);
+
+        // Without the predicate, z's loop will do as well.
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_mixed_directedness() {
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        assert_eq!(
+            grandiso::find_motifs(&motif, &complete_digraph(3)),
+            Err(grandiso::GrandIsoError::MixedDirectedness)
+        );
+    }
+
+    #[test]
+    fn test_empty_motif() {
+        let motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        let host = complete_digraph(3);
+        assert_eq!(
+            grandiso::find_motifs(&motif, &host),
+            Err(grandiso::GrandIsoError::EmptyMotif)
+        );
+        assert_eq!(
+            grandiso::count_motifs(&motif, &host),
+            Err(grandiso::GrandIsoError::EmptyMotif)
+        );
+        assert!(grandiso::find_motifs_iter(&motif, &host).is_err());
+        assert_eq!(
+            grandiso::GrandIsoError::EmptyMotif.to_string(),
+            "the motif has no nodes"
+        );
+    }
+
+    #[test]
+    fn test_disconnected_motif() {
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(2, 3, 1);
+
+        // Each of the 12 edges of K4 leaves exactly 2 edges that share no
+        // node with it.
+        let results = grandiso::find_motifs(&motif, &complete_digraph(4)).unwrap();
+        assert_eq!(results.len(), 24);
+        for mapping in &results {
+            let values: HashSet<&i8> = mapping.values().collect();
+            assert_eq!(values.len(), 4);
+        }
+
+        // An isolated node is a component of its own.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_node(2);
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 2, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 2);
+
+        // Weak connectivity is enough to make a single component.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(2, 1, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &complete_digraph(4)), Ok(24));
+    }
+
+    #[test]
+    fn test_disconnected_motif_cliques() {
+        // Two disjoint undirected triangles can be mapped onto K6 in every
+        // possible way: 6! mappings.
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)] {
+            motif.add_edge(u, v, 1);
+        }
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for u in 0..6 {
+            for v in (u + 1)..6 {
+                host.add_edge(u, v, 1);
+            }
+        }
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 720);
+
+        // But not induced: K6 has edges between the two triangles.
+        let options = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_host_connectivity() {
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(2, 3, 1);
+
+        // 0 -> 1 and 2 -> 3 are joined by 1 -> 2; 4 -> 5 is on its own.
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 2, 1);
+        host.add_edge(2, 3, 1);
+        host.add_edge(4, 5, 1);
+
+        // Every ordered pair of disjoint host edges matches.
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 8);
+
+        let options =
+            grandiso::SearchOptions::new().host_connectivity(grandiso::HostConnectivity::Connected);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        let mut results: Vec<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        results.sort();
+        assert_eq!(
+            results,
+            vec![
+                vec![(0, 0), (1, 1), (2, 2), (3, 3)],
+                vec![(0, 2), (1, 3), (2, 0), (3, 1)],
+            ]
+        );
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            2
+        );
+
+        let options = grandiso::SearchOptions::new()
+            .host_connectivity(grandiso::HostConnectivity::Disconnected);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_host_connectivity_three_components() {
+        // The components only have to be joined as a whole: here, the
+        // first two are each only joined to the third.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_node(0);
+        motif.add_node(1);
+        motif.add_node(2);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 2, 1);
+        host.add_edge(2, 1, 1);
+        host.add_node(3);
+
+        let options =
+            grandiso::SearchOptions::new().host_connectivity(grandiso::HostConnectivity::Connected);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 6);
+        for mapping in &results {
+            assert!(mapping.values().all(|n| *n != 3));
+        }
+
+        let options = grandiso::SearchOptions::new()
+            .host_connectivity(grandiso::HostConnectivity::Disconnected);
+        // Only {0, 1, 3} has no host edges between any two of its nodes.
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            6
+        );
+    }
+
+    /// The motif nodes in the order that a node predicate first sees them.
+    fn first_seen(calls: &[i8]) -> Vec<i8> {
+        let mut order = vec![];
+        for node in calls {
+            if !order.contains(node) {
+                order.push(*node);
+            }
+        }
+        order
+    }
+
+    fn star() -> DiGraphMap<i8, i8> {
+        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
+        graphmap.add_edge(0, 1, 1);
+        graphmap.add_edge(0, 2, 1);
+        graphmap.add_edge(0, 3, 1);
+        graphmap.add_edge(3, 4, 1);
+        graphmap
+    }
+
+    #[test]
+    fn test_interestingness_same_results() {
+        let motif = star();
+        let mut host = complete_digraph(6);
+        host.add_edge(6, 0, 1);
+
+        let expected: HashSet<Vec<(i8, i8)>> = grandiso::find_motifs(&motif, &host)
+            .unwrap()
+            .iter()
+            .map(sorted_pairs)
+            .collect();
+        assert_eq!(expected.len(), 720);
+
+        let custom: HashMap<i8, f32> = [(4, 2.0), (2, 1.0)].iter().cloned().collect();
+        for interestingness in [
+            grandiso::Interestingness::Degree,
+            grandiso::Interestingness::Rarest,
+            grandiso::Interestingness::Custom(custom),
+        ] {
+            let options = grandiso::SearchOptions::new()
+                .interestingness(interestingness)
+                .node_match(|_m: i8, h: i8| h != 6);
+            let results: HashSet<Vec<(i8, i8)>> =
+                grandiso::find_motifs_with_options(&motif, &host, &options)
+                    .unwrap()
+                    .iter()
+                    .map(sorted_pairs)
+                    .collect();
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn test_interestingness_custom_order() {
+        let calls = Mutex::new(vec![]);
+        let scores: HashMap<i8, f32> = [(1, 1.0), (2, 5.0), (4, 10.0)].iter().cloned().collect();
+        let options = grandiso::SearchOptions::new()
+            .interestingness(grandiso::Interestingness::Custom(scores))
+            .node_match(|m: i8, _h: i8| {
+                calls.lock().unwrap().push(m);
+                true
+            });
+        let motif = star();
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &motif, &options).unwrap(),
+            2
+        );
+        // The search is seeded with the top-scoring node, 4, and its only
+        // neighbor, 3. Next is 0. That leaves 1 and 2, of which 2 has the
+        // higher score.
+        let mut order = first_seen(&calls.lock().unwrap());
+        order[..2].sort_unstable();
+        assert_eq!(order, vec![3, 4, 0, 2, 1]);
+    }
+
+    #[test]
+    fn test_interestingness_degree_order() {
+        let calls = Mutex::new(vec![]);
+        let options = grandiso::SearchOptions::new()
+            .interestingness(grandiso::Interestingness::Degree)
+            .node_match(|m: i8, _h: i8| {
+                calls.lock().unwrap().push(m);
+                true
+            });
+        let motif = star();
+        grandiso::count_motifs_with_options(&motif, &motif, &options).unwrap();
+        // The hub goes first, then node 3, which has the next most neighbors.
+        let order = first_seen(&calls.lock().unwrap());
+        assert_eq!(&order[..2], &[0, 3]);
+    }
+
+    #[test]
+    fn test_interestingness_rarest_order() {
+        let labels: HashMap<i8, &str> = [(0, "a"), (1, "a"), (2, "b"), (3, "a")]
+            .iter()
+            .cloned()
+            .collect();
+        let calls = Mutex::new(vec![]);
+        let options = grandiso::SearchOptions::new()
+            .interestingness(grandiso::Interestingness::Rarest)
+            .node_match(|m: i8, h: i8| {
+                calls.lock().unwrap().push(m);
+                labels[&m] == labels[&h]
+            });
+
+        // A path 0 -> 1 -> 2 -> 3 in which only 2 has a rare label.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(2, 3, 1);
+
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &motif, &options).unwrap(),
+            1
+        );
+        // Computing the scores calls the predicate for each of the 4 x 4
+        // pairs of nodes; only the calls after that reflect the search order.
+        assert!(first_seen(&calls.lock().unwrap()[16..])[..2].contains(&2));
+    }
+
+    #[test]
+    fn test_edge_seeding_skips_isolated_nodes() {
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        for n in 2..100 {
+            host.add_node(n);
+        }
+
+        // Only the endpoints of the one host edge are ever considered.
+        let calls = AtomicUsize::new(0);
+        let options = grandiso::SearchOptions::new().node_match(|_m: i8, _h: i8| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            true
+        });
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 0), (1, 1)]);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_edge_seeding_direction_and_weights() {
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 0, 2);
+
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 0, 2);
+        host.add_edge(2, 3, 1);
+
+        // The seed edge's reverse edge is required, too.
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 2);
+
+        let options = grandiso::SearchOptions::new().edge_match(|m: &i8, h: &i8| m == h);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 0), (1, 1)]);
+
+        // A longer motif is still seeded and grown correctly.
+        motif.add_edge(1, 2, 3);
+        host.add_edge(1, 2, 3);
+        host.add_edge(0, 3, 3);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+
+    /// A small, irregular host: a triangle with a tail, a 4-cycle with a
+    /// chord, and a hub.
+    fn irregular_host() -> DiGraphMap<i8, i8> {
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        for &(u, v) in &[
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 4),
+            (4, 6),
+            (8, 0),
+            (8, 3),
+            (8, 5),
+            (8, 7),
+        ] {
+            host.add_edge(u, v, 1);
+        }
+        host
+    }
+
+    #[test]
+    fn test_host_signatures_same_results() {
+        let host = irregular_host();
+        let signatures = grandiso::HostSignatures::new(&host);
+        let label = |n: i8| (n % 3) as usize;
+        let labeled_signatures = grandiso::HostSignatures::with_labels(&host, label);
+
+        let mut triangle: DiGraphMap<i8, i8> = DiGraphMap::new();
+        triangle.add_edge(0, 1, 1);
+        triangle.add_edge(1, 2, 1);
+        triangle.add_edge(2, 0, 1);
+        let mut path: DiGraphMap<i8, i8> = DiGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        path.add_edge(3, 2, 1);
+
+        for motif in [triangle, path, star()].iter() {
+            let expected: HashSet<Vec<(i8, i8)>> = grandiso::find_motifs(motif, &host)
+                .unwrap()
+                .iter()
+                .map(sorted_pairs)
+                .collect();
+            let options = grandiso::SearchOptions::new().host_signatures(&signatures);
+            let results: HashSet<Vec<(i8, i8)>> =
+                grandiso::find_motifs_with_options(motif, &host, &options)
+                    .unwrap()
+                    .iter()
+                    .map(sorted_pairs)
+                    .collect();
+            assert_eq!(results, expected);
+
+            // With labels, the node predicate has to require equal labels,
+            // too.
+            let expected: HashSet<Vec<(i8, i8)>> = expected
+                .into_iter()
+                .filter(|pairs| pairs.iter().all(|(m, h)| label(*m) == label(*h)))
+                .collect();
+            let options = grandiso::SearchOptions::new()
+                .host_signatures(&labeled_signatures)
+                .motif_labels(label)
+                .node_match(|m: i8, h: i8| label(m) == label(h));
+            let results: HashSet<Vec<(i8, i8)>> =
+                grandiso::find_motifs_with_options(motif, &host, &options)
+                    .unwrap()
+                    .iter()
+                    .map(sorted_pairs)
+                    .collect();
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn test_host_signatures_prune_triangles() {
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(2, 0, 1);
+
+        let host = irregular_host();
+        let signatures = grandiso::HostSignatures::new(&host);
+        let seen = Mutex::new(HashSet::new());
+        let options = grandiso::SearchOptions::new()
+            .host_signatures(&signatures)
+            .node_match(|_m: i8, h: i8| {
+                seen.lock().unwrap().insert(h);
+                true
+            });
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            6
+        );
+        // Only nodes that lie on a triangle (ignoring directions) make it
+        // as far as the node predicate. 3 and 8 don't.
+        let expected: HashSet<i8> = [0, 1, 2, 4, 5, 6, 7].iter().cloned().collect();
+        assert!(seen.lock().unwrap().is_subset(&expected));
+    }
+
+    #[test]
+    fn test_host_signatures_prune_labels() {
+        // An "a" node that points at a "b" node.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        let motif_labels = [0, 1];
+
+        // Host nodes 0 and 1 are both "a", but only 1 points at a "b".
+        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 2, 1);
+        let host_labels = [0, 0, 1];
+
+        let signatures =
+            grandiso::HostSignatures::with_labels(&host, |h: i8| host_labels[h as usize]);
+        let calls = Mutex::new(vec![]);
+        let options = grandiso::SearchOptions::new()
+            .host_signatures(&signatures)
+            .motif_labels(|m: i8| motif_labels[m as usize])
+            .node_match(|m: i8, h: i8| {
+                calls.lock().unwrap().push((m, h));
+                motif_labels[m as usize] == host_labels[h as usize]
+            });
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 1), (1, 2)]);
+        assert!(!calls.lock().unwrap().contains(&(0, 0)));
+    }
+
+    #[test]
+    fn test_edge_lookups_hub() {
+        // A hub with an edge to every node of a long path. Looking up the
+        // edges between two host nodes must not scan the hub's neighbors
+        // every time, or this would take time quadratic in its degree.
+        let n = 20_000;
+        let mut host: Graph<(), u8> = Graph::new();
+        let nodes: Vec<NodeIndex> = (0..n).map(|_| host.add_node(())).collect();
+        let hub = host.add_node(());
+        for (i, &u) in nodes.iter().enumerate() {
+            if let Some(&next) = nodes.get(i + 1) {
+                host.add_edge(u, next, 1);
+            }
+            host.add_edge(hub, u, 2);
+        }
+        let mut motif: DiGraphMap<u32, u8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 2);
+        motif.add_edge(1, 2, 1);
+
+        // Only the paths that start at the hub have the right weights.
+        let options = grandiso::SearchOptions::new().edge_match(|m: &u8, h: &u8| m == h);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            n - 1
+        );
+        // Those are exactly the paths that aren't induced.
+        let options = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            n - 2
+        );
+    }
+
+    #[test]
+    fn test_host_signatures_hub() {
+        // A hub next to every node of a long path. Counting its triangles
+        // must not take time quadratic in its degree.
+        let n = 20_000;
+        let mut host: UnGraphMap<u32, ()> = UnGraphMap::new();
+        for u in 0..n {
+            if u + 1 < n {
+                host.add_edge(u, u + 1, ());
+            }
+            host.add_edge(n, u, ());
+        }
+        let signatures = grandiso::HostSignatures::new(&host);
+
+        // Every path node lies on two triangles, one short of the three
+        // that a node of K4 lies on; the hub has no other hub to pair up
+        // with. So nothing makes it as far as the node predicate.
+        let mut motif: UnGraphMap<u32, ()> = UnGraphMap::new();
+        for u in 0..4 {
+            for v in u + 1..4 {
+                motif.add_edge(u, v, ());
+            }
+        }
+        let seen = Mutex::new(HashSet::new());
+        let options = grandiso::SearchOptions::new()
+            .host_signatures(&signatures)
+            .node_match(|_m: u32, h: u32| {
+                seen.lock().unwrap().insert(h);
+                true
+            });
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            0
+        );
+        assert!(seen.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_orders_same_results() {
+        let host = irregular_host();
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(3, 2, 1);
+
+        let expected: HashSet<Vec<(i8, i8)>> = grandiso::find_motifs(&motif, &host)
+            .unwrap()
+            .iter()
+            .map(sorted_pairs)
+            .collect();
+        for &order in &[
+            grandiso::SearchOrder::DepthFirst,
+            grandiso::SearchOrder::Hybrid(0),
+            grandiso::SearchOrder::Hybrid(5),
+            grandiso::SearchOrder::Hybrid(1000),
+        ] {
+            let options = grandiso::SearchOptions::new().search_order(order);
+            let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+            let unique: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+            assert_eq!(unique.len(), results.len());
+            assert_eq!(unique, expected);
+            assert_eq!(
+                grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+                expected.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_depth_first_finds_first_match_sooner() {
+        // Breadth-first has to grow every partial mapping of a level before
+        // the next level, so it only finds its first clique near the end;
+        // depth-first finds one right away.
+        let motif = complete_digraph(5);
+        let host = complete_digraph(10);
+
+        let calls = AtomicUsize::new(0);
+        let count_calls = |_m: i8, _h: i8| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            true
+        };
+        let options = grandiso::SearchOptions::new().node_match(count_calls);
+        let mut iter = grandiso::find_motifs_iter_with_options(&motif, &host, &options).unwrap();
+        assert!(iter.next().is_some());
+        let breadth_first_calls = calls.swap(0, Ordering::Relaxed);
+
+        let options = grandiso::SearchOptions::new()
+            .search_order(grandiso::SearchOrder::DepthFirst)
+            .node_match(count_calls);
+        let mut iter = grandiso::find_motifs_iter_with_options(&motif, &host, &options).unwrap();
+        assert!(iter.next().is_some());
+        let depth_first_calls = calls.load(Ordering::Relaxed);
+
+        assert!(depth_first_calls * 10 < breadth_first_calls);
+    }
+
+    #[test]
+    fn test_depth_first_bounds_queue() {
+        // Breadth-first holds all 1320 partial mappings of three nodes at
+        // once. Depth-first only ever holds the seeds from one host node and
+        // the siblings of the current path, however large the host is.
+        let mut path: DiGraphMap<i8, i8> = DiGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        path.add_edge(2, 3, 1);
+        let host = complete_digraph(12);
+
+        let options = grandiso::SearchOptions::new().collect_stats(true);
+        let report = grandiso::count_motifs_report(&path, &host, &options).unwrap();
+        assert_eq!(report.stats.unwrap().peak_queue_len, 1320);
+
+        let options = options.search_order(grandiso::SearchOrder::DepthFirst);
+        let report = grandiso::count_motifs_report(&path, &host, &options).unwrap();
+        assert_eq!(report.results, 12 * 11 * 10 * 9);
+        assert!(report.stats.unwrap().peak_queue_len <= 4 * 11);
+    }
+
+    #[test]
+    fn test_limit() {
+        let motif = complete_digraph(3);
+        let host = complete_digraph(6);
+        let expected = grandiso::find_motifs(&motif, &host).unwrap();
+        assert_eq!(expected.len(), 120);
+
+        for &limit in &[0, 1, 5, 120, 500] {
+            let options = grandiso::SearchOptions::new().limit(limit);
+            let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+            assert_eq!(results, expected[..limit.min(120)].to_vec());
+            assert_eq!(
+                grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+                limit.min(120)
+            );
+        }
+
+        // The limit also applies to two-node motifs, which are complete as
+        // soon as they are seeded.
+        let options = grandiso::SearchOptions::new().limit(7);
+        let edge = complete_digraph(2);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&edge, &host, &options)
+                .unwrap()
+                .len(),
+            7
+        );
+    }
+
+    #[test]
+    fn test_limit_stops_search_early() {
+        let motif = complete_digraph(4);
+        let host = complete_digraph(10);
+
+        let calls = AtomicUsize::new(0);
+        let count_calls = |_m: i8, _h: i8| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            true
+        };
+        let options = grandiso::SearchOptions::new()
+            .search_order(grandiso::SearchOrder::DepthFirst)
+            .node_match(count_calls);
+        grandiso::count_motifs_with_options(&motif, &host, &options).unwrap();
+        let all_calls = calls.swap(0, Ordering::Relaxed);
+
+        let options = options.limit(10);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            10
+        );
+        let limited_calls = calls.load(Ordering::Relaxed);
+
+        assert!(limited_calls * 10 < all_calls);
+
+        // The same goes for seeding: one match of a single edge only needs
+        // the seeds from the first host node.
+        calls.store(0, Ordering::Relaxed);
+        let options = options.limit(1);
+        let edge = complete_digraph(2);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&edge, &host, &options).unwrap(),
+            1
+        );
+        assert_eq!(calls.load(Ordering::Relaxed), 10);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_same_results() {
+        let host = irregular_host();
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(3, 2, 1);
+
+        let expected = grandiso::find_motifs(&motif, &host).unwrap();
+        let options =
+            grandiso::SearchOptions::new().node_match(|m: i8, h: i8| m != 2 || h % 2 == 0);
+        let expected_filtered =
+            grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert!(expected_filtered.len() < expected.len());
+
+        let results = grandiso::par_find_motifs(&motif, &host).unwrap();
+        let unique: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        assert_eq!(unique.len(), results.len());
+        assert_eq!(unique, expected.iter().map(sorted_pairs).collect());
+        assert_eq!(
+            grandiso::par_count_motifs(&motif, &host).unwrap(),
+            expected.len()
+        );
+
+        let results = grandiso::par_find_motifs_with_options(&motif, &host, &options).unwrap();
+        let unique: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        assert_eq!(unique.len(), results.len());
+        assert_eq!(unique, expected_filtered.iter().map(sorted_pairs).collect());
+        assert_eq!(
+            grandiso::par_count_motifs_with_options(&motif, &host, &options).unwrap(),
+            expected_filtered.len()
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_deterministic() {
+        let motif = complete_digraph(4);
+        let host = complete_digraph(7);
+        let options = grandiso::SearchOptions::new().deterministic(true);
+
+        let expected = grandiso::find_motifs(&motif, &host).unwrap();
+        for _ in 0..5 {
+            let results = grandiso::par_find_motifs_with_options(&motif, &host, &options).unwrap();
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_limit() {
+        let motif = complete_digraph(3);
+        let host = complete_digraph(6);
+        let expected = grandiso::find_motifs(&motif, &host).unwrap();
+        let expected_set: HashSet<Vec<(i8, i8)>> = expected.iter().map(sorted_pairs).collect();
+
+        for &limit in &[0, 1, 5, 120, 500] {
+            let options = grandiso::SearchOptions::new().limit(limit);
+            let results = grandiso::par_find_motifs_with_options(&motif, &host, &options).unwrap();
+            assert_eq!(results.len(), limit.min(120));
+            assert!(results
+                .iter()
+                .all(|mapping| expected_set.contains(&sorted_pairs(mapping))));
+            assert_eq!(
+                grandiso::par_count_motifs_with_options(&motif, &host, &options).unwrap(),
+                limit.min(120)
+            );
+
+            let options = options.deterministic(true);
+            let results = grandiso::par_find_motifs_with_options(&motif, &host, &options).unwrap();
+            assert_eq!(results, expected[..limit.min(120)].to_vec());
+        }
+    }
+
+    #[test]
+    fn test_cancel() {
+        let motif = complete_digraph(3);
+        let host = complete_digraph(8);
+        let expected: HashSet<Vec<(i8, i8)>> = grandiso::find_motifs(&motif, &host)
+            .unwrap()
+            .iter()
+            .map(sorted_pairs)
+            .collect();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let options = grandiso::SearchOptions::new().cancel(cancel.clone());
+        let report = grandiso::find_motifs_report(&motif, &host, &options).unwrap();
+        assert!(!report.interrupted);
+        assert_eq!(report.results.len(), expected.len());
+
+        // Cancelling before the search starts stops it before it grows any
+        // partial mapping.
+        cancel.store(true, Ordering::Relaxed);
+        let report = grandiso::find_motifs_report(&motif, &host, &options).unwrap();
+        assert!(report.interrupted);
+        assert!(report.results.is_empty());
+        let report = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(
+            report,
+            grandiso::SearchReport {
+                results: 0,
+                interrupted: true,
+                stats: None
+            }
+        );
+
+        // Cancelling partway through keeps the mappings found until then.
+        // (The flag is only checked every few partial mappings, so the
+        // search needs to run for a while.)
+        let motif = complete_digraph(4);
+        let expected: HashSet<Vec<(i8, i8)>> = grandiso::find_motifs(&motif, &host)
+            .unwrap()
+            .iter()
+            .map(sorted_pairs)
+            .collect();
+        let calls = AtomicUsize::new(0);
+        let cancel = Arc::new(AtomicBool::new(false));
+        let cancel_later = |_m: i8, _h: i8| {
+            if calls.fetch_add(1, Ordering::Relaxed) == 500 {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            true
+        };
+        let options = grandiso::SearchOptions::new()
+            .cancel(cancel.clone())
+            .node_match(cancel_later);
+        let report = grandiso::find_motifs_report(&motif, &host, &options).unwrap();
+        assert!(report.interrupted);
+        assert!(!report.results.is_empty());
+        assert!(report.results.len() < expected.len());
+        assert!(report
+            .results
+            .iter()
+            .all(|mapping| expected.contains(&sorted_pairs(mapping))));
+    }
+
+    #[test]
+    fn test_deadline() {
+        let motif = complete_digraph(3);
+        let host = complete_digraph(8);
+
+        let options = grandiso::SearchOptions::new().deadline(Instant::now());
+        let report = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(
+            report,
+            grandiso::SearchReport {
+                results: 0,
+                interrupted: true,
+                stats: None
+            }
+        );
+        let mut iter = grandiso::find_motifs_iter_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(iter.next(), None);
+        assert!(iter.is_interrupted());
+
+        let options =
+            grandiso::SearchOptions::new().deadline(Instant::now() + Duration::from_secs(3600));
+        let report = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(
+            report,
+            grandiso::SearchReport {
+                results: 336,
+                interrupted: false,
+                stats: None
+            }
+        );
+
+        // Seeds are made as the search goes, so even a two-node motif, which
+        // is complete as soon as it is seeded, can be cut short.
+        let options = grandiso::SearchOptions::new().deadline(Instant::now());
+        let edge = complete_digraph(2);
+        let report = grandiso::find_motifs_report(&edge, &host, &options).unwrap();
+        assert!(report.interrupted);
+        assert!(report.results.is_empty());
+    }
+
+    #[test]
+    fn test_progress() {
+        // K4 in K5 is seeded from the 4 outgoing edges of each of the 5 host
+        // nodes. The 20 seeds grow into 60 partial mappings of three nodes,
+        // which grow into 120 matches.
+        let motif = complete_digraph(4);
+        let host = complete_digraph(5);
+
+        let reports = Mutex::new(vec![]);
+        let options = grandiso::SearchOptions::new()
+            .progress(1, |progress: &grandiso::SearchProgress| {
+                reports.lock().unwrap().push(*progress)
+            });
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            120
+        );
+        drop(options);
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 85);
+        for (i, progress) in reports.iter().enumerate() {
+            assert_eq!(progress.expanded, i + 1);
+            let depth = if i < 5 {
+                1
+            } else if i < 25 {
+                2
+            } else {
+                3
+            };
+            assert_eq!(progress.depth, depth);
+        }
+        assert_eq!(
+            reports[4],
+            grandiso::SearchProgress {
+                queue_len: 20,
+                expanded: 5,
+                found: 0,
+                depth: 1
+            }
+        );
+        assert_eq!(
+            reports[24],
+            grandiso::SearchProgress {
+                queue_len: 60,
+                expanded: 25,
+                found: 0,
+                depth: 2
+            }
+        );
+        assert_eq!(
+            reports[84],
+            grandiso::SearchProgress {
+                queue_len: 0,
+                expanded: 85,
+                found: 120,
+                depth: 3
+            }
+        );
+
+        let calls = AtomicUsize::new(0);
+        let options =
+            grandiso::SearchOptions::new().progress(10, |_: &grandiso::SearchProgress| {
+                calls.fetch_add(1, Ordering::Relaxed);
+            });
+        grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 8);
     }
 
-    /// Perform a single iteration of candidate-mapping growth.
-    ///
-    /// # Arguments
-    ///
-    /// * `candidate` - The partial candidate mapping
-    /// * `motif` - The motif graph network
-    /// * `host` - The host graph
-    /// * `interestingness` - A mapping of some search-order heuristic
-    ///
-    fn get_next_candidates<T, U, V, W>(
-        candidate: HashMap<T, V>,
-        motif: &DiGraphMap<T, U>,
-        host: &DiGraphMap<V, W>,
-        interestingness: &HashMap<T, f32>,
-    ) -> Vec<HashMap<T, V>>
-    where
-        T: NodeTrait + Debug,
-        U: NodeTrait,
-        V: NodeTrait + Debug,
-        W: NodeTrait,
-    {
-        // Right now we don't implement a preferred "next node" since I never
-        // used that in the Python version anyway.
-        // TODO: Could parametrize next_node.
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_progress() {
+        let motif = complete_digraph(4);
+        let host = complete_digraph(5);
 
-        // The most interesting node is defined as the node with the maximum
-        // interestingness score, which satisfies the following criteria:
-        // * If the candidate is empty, then any node will do;
-        // * If the candidate has a value, then the most interesting node must
-        //   be connected to a node in the candidate set.
-
-        // As a result, the first step is to see if the candidate mapping is empty.
-        if candidate.is_empty() {
-            // If the candidate is empty, the most interesting node is defined
-            // as the node with the maximum interestingness in general
-            let most_interesting_node: &T;
-            most_interesting_node = interestingness
+        let reports = Mutex::new(vec![]);
+        let options = grandiso::SearchOptions::new()
+            .progress(1, |progress: &grandiso::SearchProgress| {
+                reports.lock().unwrap().push(*progress)
+            });
+        assert_eq!(
+            grandiso::par_count_motifs_with_options(&motif, &host, &options).unwrap(),
+            120
+        );
+        drop(options);
+        let reports = reports.into_inner().unwrap();
+        assert_eq!(reports.len(), 85);
+        let mut expanded: Vec<usize> = reports.iter().map(|progress| progress.expanded).collect();
+        expanded.sort_unstable();
+        assert_eq!(expanded, (1..=85).collect::<Vec<_>>());
+        assert_eq!(
+            reports.iter().map(|progress| progress.found).max(),
+            Some(120)
+        );
+        assert_eq!(
+            reports
                 .iter()
-                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
-                .map(|(k, _v)| k)
-                .unwrap();
+                .filter(|progress| progress.depth == 2)
+                .count(),
+            20
+        );
+        assert!(reports[..5].iter().all(|progress| progress.depth == 1));
+    }
 
-            // Our first step is to pick a node in the motif graph and
-            // tentatively assign it to every node in the host graph.
-            let mut next_candidates = Vec::<HashMap<T, V>>::new();
-            for u in host.nodes().filter(|n| {
-                (host.neighbors_directed(*n, Incoming).count()
-                    >= motif
-                        .neighbors_directed(*most_interesting_node, Incoming)
-                        .count())
-                    && (host.neighbors_directed(*n, Outgoing).count()
-                        >= motif
-                            .neighbors_directed(*most_interesting_node, Outgoing)
-                            .count())
-            }) {
-                let mut candidate = HashMap::new();
-                candidate.insert(*most_interesting_node, u);
-                next_candidates.push(candidate);
-            }
-
-            // For the empty candidate map case, we're done. Return the list
-            // of ALL possible node mappings.
-            // TODO: Filter these by degree, or by attributes, or... anything
-            // other than returning ALL nodes. That's ridiculous.
-            return next_candidates;
-        } else {
-            // The incoming mapping already has some nodes assigned. Let's pick
-            // a motif node such that it connects to a node in the candidate
-            // already, and such that it maximizes interestingness.
-            // Note that this operation is analogous to the Python here:
-            // https://github.com/aplbrain/grandiso-networkx/blob/b5db289c7b8a681776c264014ec4f31c6431d37d/grandiso/__init__.py#L158
-            // However, in the Python implementation this is performed in
-            // several steps. In Rust, it is all possible to roll this into one
-            // big statement in order to avoid the (impossible, but Rust has no
-            // way of knowing that) memory error associated with failing to
-            // find a valid next most interesting node.
-            // Why is this impossible? Two reasons. First, we assert that the
-            // motif has only a single connected component. Second, we assert
-            // that any time this function is invoked, the candidate map is
-            // already-begun (i.e. nonempty) AS WELL AS incomplete (i.e. not
-            // the same size as |V|-motif). In these cases, it is impossible
-            // NOT to find a node that satisfies the below filtering.
-            // println!("{:#?}", candidate);
-            let most_interesting_node = motif
-                .nodes()
-                .filter(|node| !candidate.contains_key(node))
-                .max_by_key(|node| {
-                    // Given a node, count how many of its neighbors appear in the
-                    // candidate mapping. This number must be greater than zero,
-                    // assuming a motif with a single connected component.
-                    return motif
-                        .neighbors_directed(*node, Incoming)
-                        .filter(|v| candidate.contains_key(&v))
-                        .count()
-                        + motif
-                            .neighbors_directed(*node, Outgoing)
-                            .filter(|v| candidate.contains_key(&v))
-                            .count();
-                })
-                .unwrap();
-            // println!("MIN: {:#?}", most_interesting_node);
-
-            // The following is the analog of the following Python:
-            // https://github.com/aplbrain/grandiso-networkx/blob/b5db289c7b8a681776c264014ec4f31c6431d37d/grandiso/__init__.py#L192
-            // Specifically, we now have the name of a node `most_interesting_node`
-            // which we know is connected to the candidate mapping by at least
-            // one edge.
-            // In the next step, get a list of all such edges. We will do this
-            // all in one go: For each edge, we'll first identify the motif
-            // nodes in play, and then we will identify all possible nodes in
-            // the host graph s.t. these edges have valid mappings.
-            let mut required_edges = vec![];
-            let mut required_edges_in = vec![];
-            for neighboring_node in motif.neighbors_directed(most_interesting_node, Outgoing) {
-                if candidate.contains_key(&neighboring_node) {
-                    required_edges.push((most_interesting_node, neighboring_node));
-                }
+    #[test]
+    fn test_stats() {
+        let motif = complete_digraph(4);
+        let host = complete_digraph(5);
+        let report =
+            grandiso::count_motifs_report(&motif, &host, &grandiso::SearchOptions::new()).unwrap();
+        assert_eq!(report.stats, None);
+
+        let options = grandiso::SearchOptions::new().collect_stats(true);
+        let report = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(report.results, 120);
+        assert_eq!(
+            report.stats,
+            Some(grandiso::SearchStats {
+                seeds: 20,
+                candidates: vec![0, 20, 60, 120],
+                rejected: vec![0, 0, 0, 0],
+                peak_queue_len: 60,
+            })
+        );
+
+        // Every way of growing a path along the host triangle closes the
+        // triangle, which an induced path must not do.
+        let mut path: DiGraphMap<i8, i8> = DiGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        let options = grandiso::SearchOptions::new()
+            .mode(grandiso::MatchMode::Induced)
+            .collect_stats(true);
+        let report = grandiso::find_motifs_report(&path, &directed_triangle(), &options).unwrap();
+        assert!(report.results.is_empty());
+        assert_eq!(
+            report.stats,
+            Some(grandiso::SearchStats {
+                seeds: 3,
+                candidates: vec![0, 3, 0],
+                rejected: vec![0, 0, 3],
+                peak_queue_len: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_stats_canonical_rejections() {
+        // With a node predicate, only complete mappings can be checked for
+        // being canonical. Five of the six mappings onto each triangle of K4
+        // aren't, and count as rejected.
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(2, 0, 1);
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for u in 0..4 {
+            for v in (u + 1)..4 {
+                host.add_edge(u, v, 1);
             }
-            for neighboring_node in motif.neighbors_directed(most_interesting_node, Incoming) {
-                if candidate.contains_key(&neighboring_node) {
-                    required_edges_in.push((neighboring_node, most_interesting_node));
-                }
-                // TODO: Special treatment for undirected graphs needed?
-            }
-
-            // Now we have a list of all edges that must exist given the
-            // mapping so far.
-            // Now we must find candidate nodes in the host graph that have the
-            // edges that are required. We will store a list of such nodes in
-            // a vec before returning new mappings.
-            let mut candidate_host_nodes: Vec<V> = vec![];
-            let mut candidate_host_nodes_in: Vec<V> = vec![];
-
-            // It is impossible for required_edges to have length == 0, because
-            // we DEFINED the most-interesting-node to have nonzero edges.
-            // That leaves a few possibilities:
-            // In the worst-case, `required_edges` has length == 1. This is the
-            // worst because it rules out the fewest new nodes from the host.
-            // ANY unclaimed node with a single connection to our M-I-N is a
-            // valid mapping.
-            if required_edges.len() + required_edges_in.len() == 1 {
-                // If directed-edge-out:
-                if required_edges.len() == 1 {
-                    let (_m_i_n, neighbor) = required_edges.first().unwrap();
-                    candidate_host_nodes = host
-                        .neighbors_directed(*candidate.get(neighbor).unwrap(), Incoming)
-                        .collect();
-                } else if required_edges_in.len() == 1 {
-                    // if directed-edge-in:
-                    let (neighbor, _m_i_n) = required_edges_in.first().unwrap();
-                    candidate_host_nodes_in = host
-                        .neighbors_directed(*candidate.get(neighbor).unwrap(), Outgoing)
-                        .collect();
-                }
+        }
+        let options = grandiso::SearchOptions::new()
+            .symmetry(grandiso::Symmetry::Canonical)
+            .node_match(|_m: i8, _h: i8| true)
+            .collect_stats(true);
+        let report = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(report.results, 4);
+        assert_eq!(
+            report.stats,
+            Some(grandiso::SearchStats {
+                seeds: 12,
+                candidates: vec![0, 12, 4],
+                rejected: vec![0, 0, 20],
+                peak_queue_len: 12,
+            })
+        );
 
-            // println!("RE:   {:#?}", required_edges);
-            // println!("REi:  {:#?}", required_edges_in);
-            // println!("CHN:  {:#?}", candidate_host_nodes);
-            // println!("CHNi: {:#?}", candidate_host_nodes_in);
-            } else if required_edges.len() + required_edges_in.len() == 0 {
-                // py 253:
-                // If len(required_edges) is 0, something bad happened. Probably
-                // the motif has multiple connected components?
-                panic!(
-                    "Invalid motif.\
-                Does it perhaps have more than one connected component?\n\
-                \
-                Some diagnostic information:\n \
-                Required edges: {:?}\n\
-                Current M-I-N: {:?}\n\
-
-                ",
-                    required_edges.len(),
-                    most_interesting_node
-                );
-            } else {
-                // println!("required_edges:\t{:#?}", required_edges);
-                // println!("required_edges_in:\t{:#?}", required_edges_in);
-                // This is the better case; it means we can easily whittle down
-                // the total number of valid nodes here by checking to see if
-                // they have enough valid mappings in the current candidate.
-                let mut candidate_host_nodes_set = HashSet::<V>::new();
-                let mut candidate_host_nodes_set_in = HashSet::<V>::new();
-                required_edges.iter().for_each(|(_m_i_n, neighbor)| {
-                    let candidate_nodes_from_this_edge =
-                        host.neighbors_directed(*candidate.get(neighbor).unwrap(), Incoming);
-
-                    let candidate_nodes_from_this_edge_set: HashSet<V> =
-                        candidate_nodes_from_this_edge.collect();
-
-                    // !!!!!!!!!!!!!!!!!!!!! :242 in py
-                    // Candidate host nodes are the set intersection of all
-                    // previous set entries and the new entries from this edge.
-                    // If the set is empty, that implies that this is the first
-                    // edge that we are checking, so SET the candidate set.
-                    // If there are already candidates in the set, then perform
-                    // the set intersection.
-                    if candidate_host_nodes_set.is_empty() {
-                        candidate_host_nodes_set
-                            .extend(candidate_nodes_from_this_edge_set.iter().cloned());
-                    } else {
-                        candidate_host_nodes_set = candidate_host_nodes_set
-                            .iter()
-                            .filter(|n| candidate_nodes_from_this_edge_set.contains(&n))
-                            .cloned()
-                            .collect();
-                    }
-                });
-                // Same as above, for reverse edges:
-                required_edges_in.iter().for_each(|(neighbor, _m_i_n)| {
-                    let candidate_nodes_from_this_edge_in =
-                        host.neighbors_directed(*candidate.get(neighbor).unwrap(), Outgoing);
-
-                    let candidate_nodes_from_this_edge_set_in: HashSet<V> =
-                        candidate_nodes_from_this_edge_in.collect();
-                    if candidate_host_nodes_set_in.is_empty() {
-                        candidate_host_nodes_set_in
-                            .extend(candidate_nodes_from_this_edge_set_in.iter().cloned());
-                    } else {
-                        candidate_host_nodes_set_in = candidate_host_nodes_set_in
-                            .iter()
-                            .filter(|n| candidate_nodes_from_this_edge_set_in.contains(n))
-                            .cloned()
-                            .collect();
-                    }
-                });
-                candidate_host_nodes = candidate_host_nodes_set.iter().cloned().collect();
-                candidate_host_nodes_in = candidate_host_nodes_set_in.iter().cloned().collect();
+        // The seeds of a two-node motif are complete mappings already, so
+        // one of the two orientations of every host edge is rejected while
+        // seeding.
+        let mut edge: UnGraphMap<i8, i8> = UnGraphMap::new();
+        edge.add_edge(0, 1, 1);
+        let report = grandiso::count_motifs_report(&edge, &host, &options).unwrap();
+        assert_eq!(report.results, 6);
+        assert_eq!(
+            report.stats,
+            Some(grandiso::SearchStats {
+                seeds: 6,
+                candidates: vec![0, 6],
+                rejected: vec![0, 6],
+                peak_queue_len: 0,
+            })
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_stats() {
+        let motif = complete_digraph(4);
+        let host = complete_digraph(5);
+        let options = grandiso::SearchOptions::new().collect_stats(true);
+        let report = grandiso::par_count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(report.results, 120);
+        let stats = report.stats.unwrap();
+        assert_eq!(stats.seeds, 20);
+        assert_eq!(stats.candidates, vec![0, 20, 60, 120]);
+        assert_eq!(stats.rejected, vec![0, 0, 0, 0]);
+        assert!(stats.peak_queue_len >= 20);
+
+        let options = options.deterministic(true);
+        let report = grandiso::par_find_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(report.stats.unwrap().candidates, vec![0, 20, 60, 120]);
+    }
+
+    /// The host node sets of the given mappings, each sorted.
+    fn host_node_sets(results: &[HashMap<i8, i8>]) -> HashSet<Vec<i8>> {
+        results
+            .iter()
+            .map(|mapping| {
+                let mut nodes: Vec<i8> = mapping.values().cloned().collect();
+                nodes.sort_unstable();
+                nodes
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_automorphisms() {
+        let automorphisms = grandiso::find_automorphisms(&star()).unwrap();
+        let automorphisms: HashSet<Vec<(i8, i8)>> =
+            automorphisms.iter().map(sorted_pairs).collect();
+        let expected: HashSet<Vec<(i8, i8)>> = [
+            vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)],
+            vec![(0, 0), (1, 2), (2, 1), (3, 3), (4, 4)],
+        ]
+        .iter()
+        .cloned()
+        .collect();
+        assert_eq!(automorphisms, expected);
+    }
+
+    #[test]
+    fn test_canonical_undirected_triangles() {
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(2, 0, 1);
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for u in 0..5 {
+            for v in (u + 1)..5 {
+                host.add_edge(u, v, 1);
             }
+        }
 
-            let tentative_new_candidates = candidate_host_nodes.iter().map(|candidate_node| {
-                let mut new_candidate = candidate.clone();
-                new_candidate.insert(most_interesting_node, *candidate_node);
-                return new_candidate;
-            });
-            let tentative_new_candidates_in =
-                candidate_host_nodes_in.iter().map(|candidate_node| {
-                    let mut new_candidate = candidate.clone();
-                    new_candidate.insert(most_interesting_node, *candidate_node);
-                    return new_candidate;
-                });
+        let options = grandiso::SearchOptions::new().symmetry(grandiso::Symmetry::Canonical);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        // Each of the 10 triangles of K5 is found once, with its host nodes
+        // in the same order as the motif nodes.
+        assert_eq!(results.len(), 10);
+        assert_eq!(host_node_sets(&results).len(), 10);
+        assert!(results
+            .iter()
+            .all(|mapping| mapping[&0] < mapping[&1] && mapping[&1] < mapping[&2]));
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            10
+        );
+    }
 
-            // Perform one final filtering step here:
-            // We have determined that all of these NODES belong in the map,
-            // but we have not yet established that all of these edges are
-            // indeed supplied for this motif.
-            // For example, if you have edges missing between two nodes but
-            // both nodes already exist in this candidate mapping, we may have
-            // reported it as "complete" even though it's not. (e.g. A,B,C, you
-            // already have explored all of the nodes via edges AB and BC, but
-            // have not yet verified that edge CA exists.)
-            let mut new_monomorphism_candidates: Vec<HashMap<T, V>> = tentative_new_candidates
-                .filter(|candidate| {
-                    // If this doesn't have an assignment for each motif yet,
-                    // we can just return it; it's a valid mapping but incomplete.
-                    if candidate.len() != motif.node_count() {
-                        return true;
-                    } else {
-                        // Verify that all motif edges exist.
-                        motif.all_edges().all(|(u, v, _)| {
-                            let contains: bool = host.contains_edge(
-                                *candidate.get(&u).unwrap(),
-                                *candidate.get(&v).unwrap(),
-                            );
-                            if !contains {}
-                            return contains;
-                        })
-                    }
-                })
-                .collect();
+    #[test]
+    fn test_canonical_counts() {
+        // Two directed triangles on every three nodes of K5...
+        let options = grandiso::SearchOptions::new().symmetry(grandiso::Symmetry::Canonical);
+        assert_eq!(
+            grandiso::count_motifs_with_options(
+                &directed_triangle(),
+                &complete_digraph(5),
+                &options
+            )
+            .unwrap(),
+            20
+        );
 
-            new_monomorphism_candidates.extend(tentative_new_candidates_in.filter(|candidate| {
-                // If this doesn't have an assignment for each motif yet,
-                // we can just return it; it's a valid mapping but incomplete.
-                if candidate.len() != motif.node_count() {
-                    return true;
-                } else {
-                    // Verify that all motif edges exist.
-                    motif.all_edges().all(|(u, v, _)| {
-                        let contains: bool = host.contains_edge(
-                            *candidate.get(&u).unwrap(),
-                            *candidate.get(&v).unwrap(),
-                        );
-                        if !contains {}
-                        return contains;
-                    })
-                }
-            }));
-            // TODO: We ignore isomorphism here.
-            // println!("{:#?}", new_monomorphism_candidates);
-            return new_monomorphism_candidates;
+        // ...and in general, one mapping per automorphism class.
+        let host = irregular_host();
+        for motif in &[star(), directed_triangle(), complete_digraph(2)] {
+            let all = grandiso::count_motifs(motif, &host).unwrap();
+            let automorphisms = grandiso::count_automorphisms(motif).unwrap();
+            let results = grandiso::find_motifs_with_options(motif, &host, &options).unwrap();
+            assert_eq!(results.len() * automorphisms, all);
         }
     }
 
-    /// Identify all candidate subgraph monomorphisms between a motif and
-    /// a host graph.
-    ///
-    /// # Arguments
-    ///
-    /// * `motif` - The motif graph network
-    /// * `host` - The host graph
-    ///
-    pub fn find_motifs<T, U, V, W>(
-        motif: DiGraphMap<T, U>,
-        host: DiGraphMap<V, W>,
-    ) -> Vec<HashMap<T, V>>
-    where
-        T: NodeTrait + Debug,
-        U: NodeTrait,
-        V: NodeTrait + Debug,
-        W: NodeTrait,
-    {
-        // First, create the big data structures that are going to hold
-        // the state-space and the results. These have short little
-        // names because they won't be on the stage for that long, and
-        // because we're already in Generics-hell. Glad I'm not
-        // writing this in Go. (boo hiss, hot take!)
-
-        // Create an empty list for results storage, R:
-        let mut r = Vec::<HashMap<T, V>>::new();
-
-        // Create queue Q:
-        let mut q = VecDeque::<HashMap<T, V>>::new();
-
-        // Generate a nodewise lookup (map) of interestingness.
-        // For simplicity, we're just using the uniform metric.
-        // TODO: Smarter interestingness heuristics!
-        let mut interestingness = HashMap::new();
-        motif.nodes().for_each(|f| {
-            let _ = interestingness.insert(f, 1f32);
-        });
+    #[test]
+    fn test_canonical_with_node_match() {
+        // The path 0 - 1 - 2 is symmetric, but only one of its two mappings
+        // onto the host path is allowed by the predicate. That one must be
+        // kept, even though the other one would be smaller.
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        let host = motif.clone();
 
-        // Add to Q the the set of all mappings with one node.
-        // TODO: If we instead start here with the set of all
-        // edges, the total initial queue growth will be greatly
-        // reduced, which is desired.
-        let initial_mapping = HashMap::new();
-
-        q.push_back(initial_mapping);
-
-        // Now loop until the queue is empty.
-        while !q.is_empty() {
-            // Get a list of next valid candidate mappings:
-            let next_mappings =
-                get_next_candidates(q.pop_front().unwrap(), &motif, &host, &interestingness);
-
-            next_mappings.iter().for_each(|mapping| {
-                if mapping.len() == motif.node_count() {
-                    // Then this is a complete mapping; add it to the set of
-                    // valid result mappings.
-                    r.push(mapping.clone());
-                } else {
-                    // Otherwise, this is a valid partial mapping, and it
-                    // shoudl be added back into the queue:
-                    q.push_back(mapping.clone());
-                }
-            })
+        let options = grandiso::SearchOptions::new()
+            .symmetry(grandiso::Symmetry::Canonical)
+            .node_match(|m: i8, h: i8| m != 0 || h == 2);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 2), (1, 1), (2, 0)]);
+
+        let options = grandiso::SearchOptions::new().symmetry(grandiso::Symmetry::Canonical);
+        let results = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_canonical_two_nodes() {
+        // A two-node motif is complete as soon as it is seeded, so its seeds
+        // must be checked for canonicity as well: one mapping per host edge,
+        // with or without a predicate.
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0)] {
+            host.add_edge(u, v, 1);
         }
+        let options = grandiso::SearchOptions::new().symmetry(grandiso::Symmetry::Canonical);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            3
+        );
+        let options = options.node_match(|_, _| true);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            3
+        );
 
-        return r;
+        // The same goes for the two-cycle, in K4.
+        let motif = complete_digraph(2);
+        let host = complete_digraph(4);
+        let options = grandiso::SearchOptions::new()
+            .symmetry(grandiso::Symmetry::Canonical)
+            .edge_match(|_: &i8, _: &i8| true);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &host, &options).unwrap(),
+            6
+        );
     }
-}
-#[cfg(test)]
-mod tests {
 
-    use crate::grandiso;
-    use petgraph::graphmap::DiGraphMap;
+    #[test]
+    fn test_symmetry_breaking_same_results() {
+        // Without predicates, canonical mappings are found by ordering the
+        // host nodes of symmetric motif nodes; with one, by comparing the
+        // complete mappings. Both must find the same ones.
+        let mut undirected_host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (4, 2),
+            (4, 5),
+            (1, 3),
+        ] {
+            undirected_host.add_edge(u, v, 1);
+        }
+        let mut undirected_star: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (0, 2), (0, 3)] {
+            undirected_star.add_edge(u, v, 1);
+        }
+        let mut square: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 3), (3, 0)] {
+            square.add_edge(u, v, 1);
+        }
+
+        let ordered = grandiso::SearchOptions::new().symmetry(grandiso::Symmetry::Canonical);
+        let compared = grandiso::SearchOptions::new()
+            .symmetry(grandiso::Symmetry::Canonical)
+            .node_match(|_m: i8, _h: i8| true);
+        for motif in &[undirected_star, square] {
+            let expected =
+                grandiso::find_motifs_with_options(motif, &undirected_host, &compared).unwrap();
+            let results =
+                grandiso::find_motifs_with_options(motif, &undirected_host, &ordered).unwrap();
+            assert!(!results.is_empty());
+            assert_eq!(results, expected);
+        }
+        let host = irregular_host();
+        for motif in &[star(), directed_triangle(), complete_digraph(3)] {
+            let expected = grandiso::find_motifs_with_options(motif, &host, &compared).unwrap();
+            let results = grandiso::find_motifs_with_options(motif, &host, &ordered).unwrap();
+            assert_eq!(results, expected);
+        }
+    }
 
     #[test]
-    fn test_single_node() {
-        let mut graphmap: DiGraphMap<&str, &str> = DiGraphMap::new();
-        graphmap.add_node("A");
+    fn test_symmetry_breaking_prunes() {
+        // Each triangle of K6 is only grown from the seed edge between its
+        // two smallest nodes, so far fewer partial mappings are built.
+        let mut motif: UnGraphMap<i8, i8> = UnGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(1, 2, 1);
+        motif.add_edge(2, 0, 1);
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for u in 0..6 {
+            for v in (u + 1)..6 {
+                host.add_edge(u, v, 1);
+            }
+        }
+
+        let options = grandiso::SearchOptions::new().collect_stats(true);
+        let all = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(all.results, 120);
+        assert_eq!(all.stats.unwrap().seeds, 30);
+
+        let options = options.symmetry(grandiso::Symmetry::Canonical);
+        let canonical = grandiso::count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(canonical.results, 20);
+        let stats = canonical.stats.unwrap();
+        assert_eq!(stats.seeds, 15);
+        assert_eq!(stats.candidates, vec![0, 15, 20]);
+    }
+
+    #[test]
+    fn test_isomorphism() {
+        let triangle = directed_triangle();
+        let mut relabeled: Graph<(), i8> = Graph::new();
+        let a = relabeled.add_node(());
+        let b = relabeled.add_node(());
+        let c = relabeled.add_node(());
+        relabeled.add_edge(b, a, 1);
+        relabeled.add_edge(a, c, 1);
+        relabeled.add_edge(c, b, 1);
+        assert!(grandiso::is_isomorphic(&triangle, &relabeled).unwrap());
+        let isomorphisms = grandiso::find_isomorphisms(&triangle, &relabeled).unwrap();
+        assert_eq!(isomorphisms.len(), 3);
+
+        // The same numbers of nodes and edges aren't enough...
+        let mut feed_forward: DiGraphMap<i8, i8> = DiGraphMap::new();
+        feed_forward.add_edge(0, 1, 1);
+        feed_forward.add_edge(1, 2, 1);
+        feed_forward.add_edge(0, 2, 1);
+        assert!(!grandiso::is_isomorphic(&triangle, &feed_forward).unwrap());
+
+        // ...and neither is a subgraph match.
+        let complete = complete_digraph(3);
+        assert_eq!(grandiso::count_motifs(&triangle, &complete).unwrap(), 6);
+        assert!(!grandiso::is_isomorphic(&triangle, &complete).unwrap());
+        assert!(grandiso::find_isomorphisms(&triangle, &complete)
+            .unwrap()
+            .is_empty());
+        assert!(!grandiso::is_isomorphic(&triangle, &complete_digraph(4)).unwrap());
+        assert!(grandiso::is_isomorphic(&complete, &complete_digraph(3)).unwrap());
+    }
+
+    #[test]
+    fn test_isomorphism_undirected() {
+        let mut path: UnGraphMap<i8, i8> = UnGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        path.add_edge(2, 3, 1);
+        let mut reversed: UnGraphMap<i8, i8> = UnGraphMap::new();
+        reversed.add_edge(3, 2, 1);
+        reversed.add_edge(1, 2, 1);
+        reversed.add_edge(0, 1, 1);
+        let mut star: UnGraphMap<i8, i8> = UnGraphMap::new();
+        star.add_edge(0, 1, 1);
+        star.add_edge(0, 2, 1);
+        star.add_edge(0, 3, 1);
 
         assert_eq!(
-            grandiso::find_motifs(graphmap.clone(), graphmap.clone()).len(),
-            1
-        )
+            grandiso::find_isomorphisms(&path, &reversed).unwrap().len(),
+            2
+        );
+        assert!(!grandiso::is_isomorphic(&path, &star).unwrap());
+
+        // Two empty graphs are isomorphic, but an empty graph isn't
+        // isomorphic to any other, whichever way around they are given.
+        let empty: UnGraphMap<i8, i8> = UnGraphMap::new();
+        assert_eq!(grandiso::is_isomorphic(&empty, &empty), Ok(true));
+        assert_eq!(
+            grandiso::find_isomorphisms(&empty, &empty),
+            Ok(vec![HashMap::new()])
+        );
+        assert_eq!(grandiso::is_isomorphic(&path, &empty), Ok(false));
+        assert_eq!(grandiso::is_isomorphic(&empty, &path), Ok(false));
+        assert_eq!(grandiso::find_isomorphisms(&empty, &path), Ok(vec![]));
     }
+
     #[test]
-    fn test_single_edge() {
-        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
-        graphmap.add_edge(0, 1, 2);
+    fn test_isomorphism_cut_short() {
+        let triangle = directed_triangle();
+        // One isomorphism is enough, whatever the limit.
+        let options = grandiso::SearchOptions::new().limit(0);
+        assert!(grandiso::is_isomorphic_with_options(&triangle, &triangle, &options).unwrap());
 
-        let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
-        host.add_edge(0, 1, 2);
+        // A check that is stopped before it finds one has no answer.
+        let options = grandiso::SearchOptions::new().deadline(Instant::now());
+        assert_eq!(
+            grandiso::is_isomorphic_with_options(&triangle, &triangle, &options),
+            Err(grandiso::GrandIsoError::Interrupted)
+        );
+        let options = grandiso::SearchOptions::new().cancel(Arc::new(AtomicBool::new(true)));
+        assert_eq!(
+            grandiso::is_isomorphic_with_options(&triangle, &triangle, &options),
+            Err(grandiso::GrandIsoError::Interrupted)
+        );
+        // Graphs of different sizes aren't searched at all, though.
+        assert_eq!(
+            grandiso::is_isomorphic_with_options(&triangle, &complete_digraph(3), &options),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_isomorphism_with_options() {
+        let triangle = directed_triangle();
+        let mut weighted = directed_triangle();
+        *weighted.edge_weight_mut(1, 2).unwrap() = 2;
 
+        let same_weights = grandiso::SearchOptions::new()
+            .mode(grandiso::MatchMode::Monomorphism)
+            .edge_match(|a: &i8, b: &i8| a == b);
+        assert!(grandiso::is_isomorphic(&triangle, &weighted).unwrap());
+        assert!(
+            !grandiso::is_isomorphic_with_options(&triangle, &weighted, &same_weights).unwrap()
+        );
         assert_eq!(
-            grandiso::find_motifs(graphmap.clone(), host.clone()).len(),
+            grandiso::find_isomorphisms_with_options(&weighted, &weighted, &same_weights)
+                .unwrap()
+                .len(),
             1
+        );
+        // The options' mode doesn't turn the check into a subgraph search.
+        assert!(grandiso::find_isomorphisms_with_options(
+            &triangle,
+            &complete_digraph(3),
+            &same_weights
         )
+        .unwrap()
+        .is_empty());
+
+        // The mode can be used with any other search, too.
+        let isomorphism = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Isomorphism);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&triangle, &weighted, &isomorphism).unwrap(),
+            3
+        );
+        assert_eq!(
+            grandiso::count_motifs_with_options(&triangle, &irregular_host(), &isomorphism)
+                .unwrap(),
+            0
+        );
     }
 
     #[test]
-    fn test_single_edge_with_reverse_name() {
-        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
-        graphmap.add_edge(0, 1, 2);
+    fn test_homomorphisms() {
+        // A path folds onto a 2-cycle, although it has no room for a
+        // monomorphism.
+        let mut path: DiGraphMap<i8, i8> = DiGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        let two_cycle = complete_digraph(2);
+        assert_eq!(grandiso::count_motifs(&path, &two_cycle).unwrap(), 0);
+        let results = grandiso::find_homomorphisms(&path, &two_cycle).unwrap();
+        let results: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        let expected: HashSet<Vec<(i8, i8)>> =
+            [vec![(0, 0), (1, 1), (2, 0)], vec![(0, 1), (1, 0), (2, 1)]]
+                .iter()
+                .cloned()
+                .collect();
+        assert_eq!(results, expected);
+
+        // Homomorphisms of a cycle into K2 exist only for even cycles.
+        let mut k2: UnGraphMap<i8, i8> = UnGraphMap::new();
+        k2.add_edge(0, 1, 1);
+        let mut square: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 3), (3, 0)] {
+            square.add_edge(u, v, 1);
+        }
+        let mut triangle: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0)] {
+            triangle.add_edge(u, v, 1);
+        }
+        assert_eq!(grandiso::count_homomorphisms(&square, &k2).unwrap(), 2);
+        assert_eq!(grandiso::count_homomorphisms(&triangle, &k2).unwrap(), 0);
+        assert_eq!(
+            grandiso::count_homomorphisms(&triangle, &triangle).unwrap(),
+            6
+        );
+
+        // An undirected path of three nodes has 3 * 2 * 2 homomorphisms into
+        // a triangle, but only 6 monomorphisms.
+        let mut undirected_path: UnGraphMap<i8, i8> = UnGraphMap::new();
+        undirected_path.add_edge(0, 1, 1);
+        undirected_path.add_edge(1, 2, 1);
+        assert_eq!(
+            grandiso::count_motifs(&undirected_path, &triangle).unwrap(),
+            6
+        );
+        assert_eq!(
+            grandiso::count_homomorphisms(&undirected_path, &triangle).unwrap(),
+            12
+        );
+    }
+
+    #[test]
+    fn test_homomorphism_self_loops() {
+        let mut edge: DiGraphMap<i8, i8> = DiGraphMap::new();
+        edge.add_edge(0, 1, 1);
+        let mut looped: DiGraphMap<i8, i8> = DiGraphMap::new();
+        looped.add_edge(0, 0, 1);
+        let mut unlooped: DiGraphMap<i8, i8> = DiGraphMap::new();
+        unlooped.add_node(0);
+
+        // Both ends of an edge can only share a host node with a self-loop.
+        let results = grandiso::find_homomorphisms(&edge, &looped).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(sorted_pairs(&results[0]), vec![(0, 0), (1, 0)]);
+        assert_eq!(grandiso::count_homomorphisms(&edge, &unlooped).unwrap(), 0);
 
+        // A motif self-loop still needs a host self-loop.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 0, 1);
+        motif.add_edge(0, 1, 1);
         let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
-        host.add_edge(1, 0, 1);
+        host.add_edge(0, 1, 1);
+        assert_eq!(grandiso::count_homomorphisms(&motif, &host).unwrap(), 0);
+        host.add_edge(0, 0, 1);
+        let results = grandiso::find_homomorphisms(&motif, &host).unwrap();
+        let results: HashSet<Vec<(i8, i8)>> = results.iter().map(sorted_pairs).collect();
+        let expected: HashSet<Vec<(i8, i8)>> = [vec![(0, 0), (1, 0)], vec![(0, 0), (1, 1)]]
+            .iter()
+            .cloned()
+            .collect();
+        assert_eq!(results, expected);
+    }
 
+    #[test]
+    fn test_homomorphism_options() {
+        let homomorphism = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Homomorphism);
+
+        // Host signatures would rule out the host node, whose out-degree is
+        // smaller than that of the star's center.
+        let mut star: DiGraphMap<i8, i8> = DiGraphMap::new();
+        for leaf in 1..4 {
+            star.add_edge(0, leaf, 1);
+        }
+        let mut looped: DiGraphMap<i8, i8> = DiGraphMap::new();
+        looped.add_edge(0, 0, 1);
+        let signatures = grandiso::HostSignatures::new(&looped);
+        let with_signatures = grandiso::SearchOptions::new()
+            .mode(grandiso::MatchMode::Homomorphism)
+            .host_signatures(&signatures);
         assert_eq!(
-            grandiso::find_motifs(graphmap.clone(), host.clone()).len(),
+            grandiso::count_motifs_with_options(&star, &looped, &homomorphism).unwrap(),
             1
-        )
+        );
+        assert_eq!(
+            grandiso::count_motifs_with_options(&star, &looped, &with_signatures).unwrap(),
+            1
+        );
+
+        // Disconnected motif components may share host nodes.
+        let mut two_edges: DiGraphMap<i8, i8> = DiGraphMap::new();
+        two_edges.add_edge(0, 1, 1);
+        two_edges.add_edge(2, 3, 1);
+        let mut edge: DiGraphMap<i8, i8> = DiGraphMap::new();
+        edge.add_edge(0, 1, 1);
+        assert_eq!(grandiso::count_motifs(&two_edges, &edge).unwrap(), 0);
+        assert_eq!(grandiso::count_homomorphisms(&two_edges, &edge).unwrap(), 1);
+        let connected = grandiso::SearchOptions::new()
+            .mode(grandiso::MatchMode::Homomorphism)
+            .host_connectivity(grandiso::HostConnectivity::Connected);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&two_edges, &edge, &connected).unwrap(),
+            1
+        );
+
+        // Canonical homomorphisms of a symmetric path: the 6 that fold the
+        // path in half are their own mirror images; the other 6 come in
+        // mirrored pairs.
+        let mut path: UnGraphMap<i8, i8> = UnGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        let mut triangle: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0)] {
+            triangle.add_edge(u, v, 1);
+        }
+        let canonical = grandiso::SearchOptions::new()
+            .mode(grandiso::MatchMode::Homomorphism)
+            .symmetry(grandiso::Symmetry::Canonical);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&path, &triangle, &canonical).unwrap(),
+            9
+        );
+
+        // A single edge is complete as soon as it is seeded: one mapping per
+        // triangle edge, plus the one that folds it onto a self-loop.
+        let mut edge: UnGraphMap<i8, i8> = UnGraphMap::new();
+        edge.add_edge(0, 1, 1);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&edge, &triangle, &canonical).unwrap(),
+            3
+        );
+        triangle.add_edge(0, 0, 1);
+        assert_eq!(grandiso::count_homomorphisms(&edge, &triangle).unwrap(), 7);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&edge, &triangle, &canonical).unwrap(),
+            4
+        );
     }
 
     #[test]
-    fn test_one_motif_edge_with_two_edges() {
-        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
-        graphmap.add_edge(0, 1, 2);
-
+    fn test_forbidden_edges() {
+        // An edge that isn't reciprocated: only the one-way host edges.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
         let mut host: DiGraphMap<i8, i8> = DiGraphMap::new();
-        host.add_edge(0, 1, 2);
-        host.add_edge(1, 0, 2);
+        host.add_edge(0, 1, 1);
+        host.add_edge(1, 0, 1);
+        host.add_edge(1, 2, 1);
+        host.add_edge(2, 3, 1);
+        assert_eq!(grandiso::count_motifs(&motif, &host).unwrap(), 4);
+        let options = grandiso::SearchOptions::new().forbidden_edge(1, 0);
+        let mut results: Vec<Vec<(i8, i8)>> =
+            grandiso::find_motifs_with_options(&motif, &host, &options)
+                .unwrap()
+                .iter()
+                .map(sorted_pairs)
+                .collect();
+        results.sort();
+        assert_eq!(results, vec![vec![(0, 1), (1, 2)], vec![(0, 2), (1, 3)]]);
 
+        // The forbidden edge rules out partial mappings as soon as both of
+        // its endpoints are mapped, before the rest of the motif is.
+        let mut path: DiGraphMap<i8, i8> = DiGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        path.add_edge(2, 3, 1);
+        let host = complete_digraph(5);
+        assert_eq!(grandiso::count_motifs(&path, &host).unwrap(), 120);
+        let options = grandiso::SearchOptions::new()
+            .forbidden_edge(3, 1)
+            .collect_stats(true);
+        let report = grandiso::count_motifs_report(&path, &host, &options).unwrap();
         assert_eq!(
-            grandiso::find_motifs(graphmap.clone(), host.clone()).len(),
-            2
-        )
+            report,
+            grandiso::SearchReport {
+                results: 0,
+                interrupted: false,
+                stats: Some(grandiso::SearchStats {
+                    seeds: 20,
+                    candidates: vec![0, 20, 0, 0],
+                    rejected: vec![0, 0, 60, 0],
+                    peak_queue_len: 20,
+                }),
+            }
+        );
     }
 
     #[test]
-    fn test_two_edges() {
-        let mut graphmap: DiGraphMap<i8, i8> = DiGraphMap::new();
-        graphmap.add_edge(0, 1, 2);
-        graphmap.add_edge(1, 2, 3);
+    fn test_forbidden_edges_undirected() {
+        // Open paths of length two: the host triangle closes every path
+        // through it, so only the ones that end at node 3 are left.
+        let mut path: UnGraphMap<i8, i8> = UnGraphMap::new();
+        path.add_edge(0, 1, 1);
+        path.add_edge(1, 2, 1);
+        let mut host: UnGraphMap<i8, i8> = UnGraphMap::new();
+        for &(u, v) in &[(0, 1), (1, 2), (2, 0), (2, 3)] {
+            host.add_edge(u, v, 1);
+        }
+        assert_eq!(grandiso::count_motifs(&path, &host).unwrap(), 10);
+        let induced = grandiso::SearchOptions::new().mode(grandiso::MatchMode::Induced);
+        let expected = grandiso::find_motifs_with_options(&path, &host, &induced).unwrap();
+        assert_eq!(expected.len(), 4);
+        for &(u, v) in &[(0, 2), (2, 0)] {
+            let options = grandiso::SearchOptions::new().forbidden_edge(u, v);
+            assert_eq!(
+                grandiso::find_motifs_with_options(&path, &host, &options).unwrap(),
+                expected
+            );
+        }
+    }
 
+    #[test]
+    fn test_forbidden_edges_canonical() {
+        // Forbidding 1 -> 2 tells the two leaves of the out-star apart, so
+        // swapping them no longer maps a valid mapping onto another one.
+        let mut motif: DiGraphMap<i8, i8> = DiGraphMap::new();
+        motif.add_edge(0, 1, 1);
+        motif.add_edge(0, 2, 1);
+        let mut host = motif.clone();
+        host.add_edge(1, 2, 1);
+        let options = grandiso::SearchOptions::new().forbidden_edge(1, 2);
+        let expected = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(expected.len(), 1);
+        assert_eq!(sorted_pairs(&expected[0]), vec![(0, 0), (1, 2), (2, 1)]);
+        let options = options.symmetry(grandiso::Symmetry::Canonical);
         assert_eq!(
-            grandiso::find_motifs(graphmap.clone(), graphmap.clone()).len(),
-            1
-        )
+            grandiso::find_motifs_with_options(&motif, &host, &options).unwrap(),
+            expected
+        );
     }
 
     #[test]
-    fn test_directed_triangles() {
-        let mut graphmap: DiGraphMap<i8, &str> = DiGraphMap::new();
-        graphmap.add_edge(0, 1, "3");
-        graphmap.add_edge(1, 2, "3");
-        graphmap.add_edge(2, 0, "3");
-        let mut graphmap_host: DiGraphMap<&str, &str> = DiGraphMap::new();
-        graphmap_host.add_edge("A", "B", "3");
-        graphmap_host.add_edge("B", "C", "3");
-        graphmap_host.add_edge("C", "A", "3");
+    fn test_forbidden_edges_unknown_node() {
+        let motif = directed_triangle();
+        let options = grandiso::SearchOptions::new().forbidden_edge(0, 7);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&motif, &motif, &options),
+            Err(grandiso::GrandIsoError::UnknownForbiddenNode)
+        );
+        assert_eq!(
+            grandiso::is_isomorphic_with_options(&motif, &motif, &options),
+            Err(grandiso::GrandIsoError::UnknownForbiddenNode)
+        );
+    }
 
-        let results = grandiso::find_motifs(graphmap.clone(), graphmap_host.clone());
+    #[test]
+    fn test_forbidden_edges_motif_edge() {
+        // No mapping can both have and not have a host edge for 0 -> 1.
+        let motif = directed_triangle();
+        let options = grandiso::SearchOptions::new().forbidden_edge(0, 1);
+        assert_eq!(
+            grandiso::find_motifs_with_options(&motif, &complete_digraph(4), &options),
+            Err(grandiso::GrandIsoError::ForbiddenMotifEdge)
+        );
+        // The reverse edge is fine in a directed motif...
+        let options = grandiso::SearchOptions::new().forbidden_edge(1, 0);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&motif, &motif, &options),
+            Ok(3)
+        );
 
-        assert_eq!(results.len(), 6)
+        // ...but in an undirected one, it's the same edge.
+        let mut edge: UnGraphMap<i8, i8> = UnGraphMap::new();
+        edge.add_edge(0, 1, 1);
+        let options = grandiso::SearchOptions::new().forbidden_edge(1, 0);
+        assert_eq!(
+            grandiso::count_motifs_with_options(&edge, &edge, &options),
+            Err(grandiso::GrandIsoError::ForbiddenMotifEdge)
+        );
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_directed_triangles_fully_connected() {
-        let mut graphmap: DiGraphMap<&str, &str> = DiGraphMap::new();
-        graphmap.add_edge("0", "1", "3");
-        graphmap.add_edge("1", "2", "3");
-        graphmap.add_edge("2", "0", "3");
-        let mut graphmap_host: DiGraphMap<&str, &str> = DiGraphMap::new();
-        graphmap_host.add_edge("A", "B", "3");
-        graphmap_host.add_edge("B", "A", "3");
-        graphmap_host.add_edge("B", "C", "3");
-        graphmap_host.add_edge("C", "B", "3");
-        graphmap_host.add_edge("C", "A", "3");
-        graphmap_host.add_edge("A", "C", "3");
+    fn test_parallel_cancel() {
+        let motif = complete_digraph(3);
+        let host = complete_digraph(8);
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let options = grandiso::SearchOptions::new().cancel(cancel.clone());
+        let report = grandiso::par_find_motifs_report(&motif, &host, &options).unwrap();
+        assert!(report.interrupted);
+        assert!(report.results.is_empty());
+        let report = grandiso::par_count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(
+            report,
+            grandiso::SearchReport {
+                results: 0,
+                interrupted: true,
+                stats: None
+            }
+        );
+
+        cancel.store(false, Ordering::Relaxed);
+        let report = grandiso::par_count_motifs_report(&motif, &host, &options).unwrap();
+        assert_eq!(
+            report,
+            grandiso::SearchReport {
+                results: 336,
+                interrupted: false,
+                stats: None
+            }
+        );
+        let options = options.deterministic(true);
+        let report = grandiso::par_find_motifs_report(&motif, &host, &options).unwrap();
+        assert!(!report.interrupted);
+        assert_eq!(report.results.len(), 336);
+    }
 
-        let results = grandiso::find_motifs(graphmap.clone(), graphmap_host.clone());
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_canonical() {
+        let options = grandiso::SearchOptions::new().symmetry(grandiso::Symmetry::Canonical);
+        let motif = directed_triangle();
+        let host = complete_digraph(6);
+        let expected = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(expected.len(), 40);
+        assert_eq!(
+            grandiso::par_count_motifs_with_options(&motif, &host, &options).unwrap(),
+            40
+        );
+        let options = options.deterministic(true);
+        assert_eq!(
+            grandiso::par_find_motifs_with_options(&motif, &host, &options).unwrap(),
+            expected
+        );
+    }
 
-        assert_eq!(results.len(), 12)
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_forbidden_edges() {
+        let motif = star();
+        let host = irregular_host();
+        let options = grandiso::SearchOptions::new()
+            .forbidden_edge(1, 4)
+            .forbidden_edge(2, 4);
+        let expected = grandiso::find_motifs_with_options(&motif, &host, &options).unwrap();
+        assert_eq!(expected.len(), 16);
+        assert_eq!(
+            grandiso::par_count_motifs_with_options(&motif, &host, &options).unwrap(),
+            expected.len()
+        );
+        let options = options.deterministic(true);
+        assert_eq!(
+            grandiso::par_find_motifs_with_options(&motif, &host, &options).unwrap(),
+            expected
+        );
     }
 }